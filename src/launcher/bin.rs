@@ -0,0 +1,31 @@
+use std::{env, process::ExitCode, time::Duration};
+
+use tiny::{
+    app::TinyApp,
+    cli::{Args, Cli, Flag},
+    config::Config,
+    flow::{GameLauncher, GameRegistry, LauncherFlow},
+    snake::flows::SnakeLauncher,
+    sokoban::flows::SokobanLauncher,
+};
+
+/// Registers every game with the user's settings, so that mistakes are reported before the terminal is set up.
+fn load_app(args: &Args) -> Result<TinyApp, String> {
+    let registry = GameRegistry::new()
+        .with_game(SokobanLauncher::from_config(&Config::load("sokoban")?)?)
+        .with_game(SnakeLauncher::from_config(&Config::load("snake")?)?);
+
+    // The frame rate and the pause keys follow the running game.
+    Ok(TinyApp::new()
+        .with_fixed_timestep(Duration::from_secs(1) / 60)
+        .with_args(args)?
+        .with_flow(LauncherFlow::new("Tiny games", registry)))
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::new("tiny", env!("CARGO_PKG_VERSION"))
+        .with_about("Lists the tiny games and starts the selected one.")
+        .with_flags(&[Flag::NoColor, Flag::Replay]);
+
+    cli.run(env::args().skip(1), load_app)
+}
@@ -1,14 +1,47 @@
-mod flows;
-mod snake;
+use std::{env, process::ExitCode, time::Duration};
 
-use flows::SnakeLauncher;
-use tiny::{app::TinyApp, flow::IntroFlow};
+use tiny::{
+    app::TinyApp,
+    cli::{Args, Cli, Flag},
+    config::Config,
+    flow::{GameLauncher, IntroFlow},
+    snake::flows::SnakeLauncher,
+};
 
-fn main() {
-    let controls = [("wasd", "move"), ("q", "quit")];
+/// Sets up the app from the command line and the user's settings,
+/// so that mistakes are reported before the terminal is set up.
+fn load_app(args: &Args) -> Result<TinyApp, String> {
+    let mut config = Config::load("snake")?;
+    args.apply_to(&mut config)?;
+    let launcher = SnakeLauncher::from_config(&config)?;
 
-    let mut app =
-        TinyApp::new().with_flow(IntroFlow::<SnakeLauncher>::new("Snake").with_controls(&controls));
+    let app = TinyApp::new()
+        .with_target_fps(launcher.target_fps())
+        .with_args(args)?
+        .with_fixed_timestep(Duration::from_secs(1) / 60)
+        .with_pause_keys(launcher.pause_keys());
+    if args.no_intro {
+        let game = launcher
+            .launch_game(&launcher.default_context())
+            .map_err(|error| error.to_string())?;
+        return Ok(app.with_boxed_flow(game));
+    }
 
-    app.run();
+    Ok(app.with_flow(IntroFlow::new("Snake", launcher)))
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::new("snake", env!("CARGO_PKG_VERSION"))
+        .with_about("Eat the food without running into the walls or yourself.")
+        .with_flags(&[
+            Flag::Seed,
+            Flag::Size,
+            Flag::Difficulty,
+            Flag::Fps,
+            Flag::NoColor,
+            Flag::NoIntro,
+            Flag::Replay,
+        ]);
+
+    cli.run(env::args().skip(1), load_app)
 }
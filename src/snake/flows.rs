@@ -1,80 +1,175 @@
-use crate::snake::{Cell, GameState, Grid, Snake, SnakePart, UpdateResult};
-use tiny::app::Time;
-use tiny::prelude::*;
+use super::settings::Settings;
+use super::snake::{Cell, GameState, Grid, Snake, SnakePart, UpdateResult};
+use crate::app::Time;
+use crate::banner::Font;
+use crate::prelude::*;
 
-use tiny::flow::{GameLauncher, QuitFlow};
+use std::{any::Any, rc::Rc};
+
+use crate::config::Config;
+use crate::flow::{
+    FlowError, FlowResult, GameLauncher, LaunchContext, LaunchOption, MenuFlow, Transition,
+};
+use crate::input::{InputMap, InputPolicy};
+use crate::viewport::Viewport;
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Command {
     Move(i32, i32),
+    /// Handled by the app, which stops the time until it is pressed again.
+    Pause,
+    Menu,
     Quit,
-    Unknown,
 }
 
-pub fn translate_input(input: Key) -> Command {
-    match input {
-        Key::W => Command::Move(0, -1),
-        Key::A => Command::Move(-1, 0),
-        Key::S => Command::Move(0, 1),
-        Key::D => Command::Move(1, 0),
-        Key::Q => Command::Quit,
-        _ => Command::Unknown,
-    }
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum MenuAction {
+    Resume,
+    Restart,
+    Quit,
+}
+
+pub fn input_map() -> InputMap<Command> {
+    InputMap::new()
+        .bind("up", "move", [Key::W, Key::Up], Command::Move(0, -1))
+        .bind("left", "move", [Key::A, Key::Left], Command::Move(-1, 0))
+        .bind("down", "move", [Key::S, Key::Down], Command::Move(0, 1))
+        .bind("right", "move", [Key::D, Key::Right], Command::Move(1, 0))
+        .bind("pause", "pause", [Key::Space], Command::Pause)
+        .bind("menu", "menu", [Key::P, Key::Escape], Command::Menu)
+        .bind("quit", "quit", [Key::Q], Command::Quit)
+}
+
+pub struct SnakeLauncher {
+    settings: Rc<Settings>,
 }
 
-pub struct SnakeLauncher;
 impl GameLauncher for SnakeLauncher {
-    fn new() -> Self {
-        SnakeLauncher
+    fn from_config(config: &Config) -> Result<Self, String> {
+        Ok(SnakeLauncher {
+            settings: Rc::new(Settings::from_config(config)?),
+        })
+    }
+
+    fn name(&self) -> &str {
+        "Snake"
+    }
+
+    fn controls(&self) -> Vec<(String, String)> {
+        self.settings.common.input_map.controls()
+    }
+
+    fn target_fps(&self) -> u32 {
+        self.settings.common.fps
+    }
+
+    fn pause_keys(&self) -> Vec<KeyEvent> {
+        self.settings.common.input_map.keys("pause")
     }
 
-    fn launch_game(&self) -> Box<dyn Flow> {
-        Box::new(GameFlow::new())
+    fn font(&self) -> Font {
+        self.settings.common.font.clone()
+    }
+
+    fn default_context(&self) -> LaunchContext {
+        LaunchContext {
+            seed: self.settings.seed,
+            difficulty: self.settings.difficulty,
+            ..LaunchContext::default()
+        }
+    }
+
+    fn launch_options(&self) -> Vec<LaunchOption> {
+        vec![LaunchOption::Difficulty]
+    }
+
+    fn launch_game(&self, context: &LaunchContext) -> Result<Box<dyn Flow>, FlowError> {
+        let mut settings = (*self.settings).clone();
+        settings.seed = context.seed;
+        settings.difficulty = context.difficulty;
+
+        Ok(Box::new(GameFlow::new(Rc::new(settings))))
     }
 }
 
 pub struct GameFlow {
     state: GameState,
+    /// Scrolls boards larger than the terminal to follow the head.
+    viewport: Viewport,
+    settings: Rc<Settings>,
 }
 
 impl GameFlow {
-    pub fn new() -> Self {
+    pub fn new(settings: Rc<Settings>) -> Self {
         GameFlow {
-            state: GameState::new(),
+            state: GameState::new(settings.grid_size, settings.seed, settings.difficulty),
+            viewport: Viewport::new(settings.grid_size),
+            settings,
         }
     }
 }
 
 impl Flow for GameFlow {
-    fn render(&self) {
-        self.state.render();
+    fn render(&self, canvas: &mut Canvas) {
+        self.state.render(canvas, &self.viewport, &self.settings);
     }
 
-    fn update(&mut self, time: &Time) -> Option<Box<dyn Flow>> {
+    fn update(&mut self, time: &Time) -> FlowResult {
         if self.state.update(time.frame_delta_time) == UpdateResult::Collision {
-            return Some(Box::new(CollisionAnimSequence::new(
+            return Ok(Transition::Replace(Box::new(CollisionAnimSequence::new(
                 &self.state,
+                self.viewport,
                 time.time_since_startup,
-            )));
+                self.settings.clone(),
+            ))));
         }
 
-        None
+        self.viewport
+            .resize(self.settings.common.tileset.cells_in(time.viewport_size));
+        self.viewport.follow(self.state.snake.head_position());
+
+        Ok(Transition::None)
     }
 
-    fn handle_key(&mut self, key: Key) -> Option<Box<dyn Flow>> {
-        let command = translate_input(key);
+    fn handle_key(&mut self, key: KeyEvent) -> FlowResult {
+        let Some(command) = self.settings.common.input_map.get(key) else {
+            return Ok(Transition::None);
+        };
+
         match command {
             Command::Move(dx, dy) => {
                 let new_direction = Direction { x: dx, y: dy };
                 self.state.set_direction(new_direction);
             }
+            Command::Pause => {}
+            Command::Menu => {
+                return Ok(Transition::Push(Box::new(
+                    MenuFlow::new("Paused")
+                        .with_option("Resume", MenuAction::Resume)
+                        .with_option("Restart", MenuAction::Restart)
+                        .with_option("Quit", MenuAction::Quit),
+                )));
+            }
             Command::Quit => {
-                return Some(Box::new(QuitFlow {}));
+                return Ok(Transition::Pop);
             }
-            _ => {}
         }
 
-        None
+        Ok(Transition::None)
+    }
+
+    fn handle_result(&mut self, result: Box<dyn Any>) -> FlowResult {
+        Ok(match result.downcast_ref::<MenuAction>() {
+            Some(MenuAction::Restart) => {
+                Transition::Replace(Box::new(GameFlow::new(self.settings.clone())))
+            }
+            Some(MenuAction::Quit) => Transition::Pop,
+            Some(MenuAction::Resume) | None => Transition::None,
+        })
+    }
+
+    fn input_policy(&self) -> InputPolicy {
+        InputPolicy::CoalesceRepeats
     }
 }
 
@@ -84,10 +179,17 @@ struct CollisionAnimSequence {
     grid: Grid,
     foods: Vec<Position>,
     snake_visible: bool,
+    viewport: Viewport,
+    settings: Rc<Settings>,
 }
 
 impl CollisionAnimSequence {
-    fn new(game_state: &GameState, start_time: Duration) -> Self {
+    fn new(
+        game_state: &GameState,
+        viewport: Viewport,
+        start_time: Duration,
+        settings: Rc<Settings>,
+    ) -> Self {
         // TODO: Would like to avoid clone here.
         CollisionAnimSequence {
             anim_start_time: start_time,
@@ -95,42 +197,58 @@ impl CollisionAnimSequence {
             grid: game_state.grid.clone(),
             foods: game_state.foods.clone(),
             snake_visible: false,
+            viewport,
+            settings,
         }
     }
 }
 
 impl Flow for CollisionAnimSequence {
-    fn update(&mut self, time: &Time) -> Option<Box<dyn Flow>> {
+    fn update(&mut self, time: &Time) -> FlowResult {
         if time.time_since_startup > self.anim_start_time + Duration::new(3, 0) {
-            return Some(Box::new(GameFlow::new()));
+            return Ok(Transition::Replace(Box::new(GameFlow::new(
+                self.settings.clone(),
+            ))));
         }
 
-        self.snake_visible =
-            (3 * (time.time_since_startup - self.anim_start_time)).as_secs() % 2 == 0;
+        self.snake_visible = (3 * (time.time_since_startup - self.anim_start_time))
+            .as_secs()
+            .is_multiple_of(2);
 
-        None
+        Ok(Transition::None)
     }
 
-    fn render(&self) {
+    fn render(&self, canvas: &mut Canvas) {
         // Render the level.
         let mut render = self.grid.clone();
 
         // Render the snake.
         if self.snake_visible {
             for part in self.snake.parts.iter() {
-                render.set_cell(*part, Cell::Snake(SnakePart::Body));
+                render.set(*part, Cell::Snake(SnakePart::Body));
             }
-            render.set_cell(
+            render.set(
                 *self.snake.parts.front().unwrap(),
                 Cell::Snake(SnakePart::Head),
             );
         }
 
         for food in &self.foods {
-            render.set_cell(*food, Cell::Food);
+            render.set(*food, Cell::Food);
         }
 
-        // Print all.
-        render.print();
+        // Draw all.
+        let theme = &self.settings.theme;
+        let tileset = self.settings.common.tileset;
+        let walls = self.settings.common.walls;
+        tileset.draw_grid(canvas, &render, &self.viewport, walls, |cell| {
+            theme.style(*cell)
+        });
+
+        let style = Style::new().fg(Color::BrightRed).bold();
+        self.settings
+            .common
+            .font
+            .draw_centered(&mut canvas.layer(1), "GAME OVER", style);
     }
 }
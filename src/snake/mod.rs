@@ -0,0 +1,6 @@
+//! Snake, played on its own by the `snake` binary or from the `tiny` launcher.
+
+pub mod flows;
+mod settings;
+#[allow(clippy::module_inception)]
+mod snake;
@@ -0,0 +1,78 @@
+use crate::{
+    config::{CommonSettings, Config},
+    flow::Difficulty,
+    math::Size,
+};
+
+use super::{
+    flows::{input_map, Command},
+    snake::Theme,
+};
+
+/// The settings of the config file next to the common ones.
+const KNOWN_SETTINGS: [&str; 8] = [
+    "seed",
+    "difficulty",
+    "grid.width",
+    "grid.height",
+    "colors.wall",
+    "colors.food",
+    "colors.head",
+    "colors.body",
+];
+
+/// Game settings, the defaults changed by the user's config.
+#[derive(Clone)]
+pub struct Settings {
+    pub common: CommonSettings<Command>,
+    pub theme: Theme,
+    /// Size of the board, walls included.
+    pub grid_size: Size,
+    /// Seed of the food placement, random when not set.
+    pub seed: Option<u64>,
+    /// How fast the snake moves.
+    pub difficulty: Difficulty,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        // The snake moves smoothly between cells at 60 frames per second.
+        Settings {
+            common: CommonSettings::new(60, input_map()),
+            theme: Theme::default(),
+            grid_size: Size::new(60, 30),
+            seed: None,
+            difficulty: Difficulty::default(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn from_config(config: &Config) -> Result<Self, String> {
+        let mut settings = Settings::default();
+        settings.common.read(config, &KNOWN_SETTINGS)?;
+
+        let theme = &mut settings.theme;
+        theme.wall = config.get_foreground("colors.wall", theme.wall)?;
+        theme.food = config.get_foreground("colors.food", theme.food)?;
+        theme.head = config.get_foreground("colors.head", theme.head)?;
+        theme.body = config.get_foreground("colors.body", theme.body)?;
+
+        // The snake starts along the top wall and needs room to turn.
+        if let Some(width) = config.get_integer("grid.width", 10..=500)? {
+            settings.grid_size.width = width as usize;
+        }
+        if let Some(height) = config.get_integer("grid.height", 5..=500)? {
+            settings.grid_size.height = height as usize;
+        }
+
+        if let Some(seed) = config.get_integer("seed", 0..=i64::MAX)? {
+            settings.seed = Some(seed as u64);
+        }
+        if let Some(difficulty) = config.get_str("difficulty")? {
+            settings.difficulty = difficulty.parse()?;
+        }
+
+        Ok(settings)
+    }
+}
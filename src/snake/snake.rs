@@ -1,13 +1,22 @@
-use std::{collections::VecDeque, iter};
+use std::collections::VecDeque;
 
-use rand::Rng;
-use tiny::prelude::*;
+use crate::{
+    flow::Difficulty,
+    prelude::*,
+    tileset::{Tile, Tileset},
+    viewport::Viewport,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::settings::Settings;
 
 #[derive(Clone)]
 pub struct Snake {
     direction: Direction,
     pub parts: VecDeque<Position>,
     accumulated_distance: f32,
+    /// Factor of the speed, which grows with the length of the snake.
+    speed: f32,
 }
 
 enum AdvanceResult {
@@ -16,7 +25,7 @@ enum AdvanceResult {
 }
 
 impl Snake {
-    fn new(head_position: Position, length: usize) -> Self {
+    fn new(head_position: Position, length: usize, speed: f32) -> Self {
         let Position { x, y } = head_position;
         let mut parts = VecDeque::from([head_position]);
         for i in 0..length {
@@ -30,10 +39,11 @@ impl Snake {
             direction: Direction { x: 1, y: 0 },
             parts,
             accumulated_distance: 0.0f32,
+            speed,
         }
     }
 
-    fn head_position(&self) -> Position {
+    pub fn head_position(&self) -> Position {
         assert!(!self.parts.is_empty());
 
         *self.parts.front().unwrap()
@@ -42,12 +52,15 @@ impl Snake {
     fn advance(&mut self, grid: &Grid, delta_time: Duration) -> AdvanceResult {
         assert!(!self.parts.is_empty());
 
-        let speed = 1.0f32 * self.parts.len() as f32;
+        let speed = self.speed * self.parts.len() as f32;
         self.accumulated_distance += speed * delta_time.as_secs_f32();
         if self.accumulated_distance > 1.0f32 {
             let new_head_position = *self.parts.front().unwrap() + self.direction;
 
-            if !grid.is_empty(new_head_position) {
+            if !grid
+                .get(new_head_position)
+                .is_some_and(|cell| cell.is_free())
+            {
                 return AdvanceResult::Collision;
             }
 
@@ -62,7 +75,7 @@ impl Snake {
             self.accumulated_distance = 0.0f32;
         }
 
-        return AdvanceResult::Ok(*self.parts.back().unwrap());
+        AdvanceResult::Ok(*self.parts.back().unwrap())
     }
 
     fn grow(&mut self, tail_position: Position) {
@@ -91,46 +104,77 @@ pub enum Cell {
     Snake(SnakePart),
 }
 
-impl From<Cell> for char {
-    fn from(cell: Cell) -> Self {
-        match cell {
-            Cell::Empty => ' ',
-            Cell::Wall => '#',
-            Cell::Food => 'Q',
-            Cell::Snake(SnakePart::Head) => '@',
-            Cell::Snake(SnakePart::Body) => 'a',
+impl Tile for Cell {
+    fn glyph(&self, tileset: Tileset) -> &str {
+        match tileset {
+            Tileset::Ascii => match self {
+                Cell::Empty => " ",
+                Cell::Wall => "#",
+                Cell::Food => "Q",
+                Cell::Snake(SnakePart::Head) => "@",
+                Cell::Snake(SnakePart::Body) => "a",
+            },
+            Tileset::Unicode => match self {
+                Cell::Empty => " ",
+                Cell::Wall => "█",
+                Cell::Food => "●",
+                Cell::Snake(SnakePart::Head) => "◆",
+                Cell::Snake(SnakePart::Body) => "■",
+            },
+            // Shaded body so that the snake stands out from solid walls without colours.
+            Tileset::Wide => match self {
+                Cell::Empty => "  ",
+                Cell::Wall => "██",
+                Cell::Food => "🍎",
+                Cell::Snake(SnakePart::Head) => "🐍",
+                Cell::Snake(SnakePart::Body) => "▓▓",
+            },
         }
     }
+
+    fn is_wall(&self) -> bool {
+        *self == Cell::Wall
+    }
 }
 
-#[derive(Debug, Clone)]
-pub struct Grid {
-    grid: Vec<Cell>,
-    width: usize,
-    height: usize,
+/// Styles of the cells, their colours can be set in the `[colors]` table of the config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub wall: Style,
+    pub food: Style,
+    pub head: Style,
+    pub body: Style,
 }
 
-impl Grid {
-    pub fn cell_at(&self, position: Position) -> Cell {
-        self.grid[position.x as usize + self.width * position.y as usize]
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            wall: Style::new().fg(Color::BrightBlack),
+            food: Style::new().fg(Color::BrightRed).bold(),
+            head: Style::new().fg(Color::BrightGreen).bold(),
+            body: Style::new().fg(Color::Green),
+        }
     }
+}
 
-    pub fn set_cell(&mut self, position: Position, value: Cell) {
-        self.grid[position.x as usize + self.width * position.y as usize] = value;
+impl Theme {
+    pub fn style(&self, cell: Cell) -> Style {
+        match cell {
+            Cell::Empty => Style::new(),
+            Cell::Wall => self.wall,
+            Cell::Food => self.food,
+            Cell::Snake(SnakePart::Head) => self.head,
+            Cell::Snake(SnakePart::Body) => self.body,
+        }
     }
+}
 
-    pub fn is_empty(&self, position: Position) -> bool {
-        let cell = self.cell_at(position);
-        cell == Cell::Empty || cell == Cell::Food
-    }
+pub type Grid = crate::grid::Grid<Cell>;
 
-    pub fn print(&self) {
-        let mut output_string = String::with_capacity(self.grid.len() + self.height);
-        for line in self.grid.chunks(self.width as usize) {
-            output_string.extend(line.iter().map(|cell| char::from(*cell)));
-            output_string.push('\n');
-        }
-        print!("{output_string}");
+impl Cell {
+    /// Whether the snake can move onto the cell.
+    pub fn is_free(self) -> bool {
+        self == Cell::Empty || self == Cell::Food
     }
 }
 
@@ -141,32 +185,24 @@ pub struct GameState {
     pub foods: Vec<Position>,
 }
 
-fn initialize_level(size: (usize, usize)) -> Grid {
-    let interior_size = (size.0 - 2, size.1 - 2);
-
-    let mut level = Vec::with_capacity((size.0 * size.1) as usize);
-    level.extend(iter::repeat(Cell::Wall).take(size.0 as usize));
-    for _ in 0..interior_size.1 {
-        level.extend(
-            iter::once(Cell::Wall).chain(
-                iter::repeat(Cell::Empty)
-                    .take(interior_size.0 as usize)
-                    .chain(iter::once(Cell::Wall)),
-            ),
-        );
-    }
-    level.extend(iter::repeat(Cell::Wall).take(size.0 as usize));
-
-    Grid {
-        grid: level,
-        width: size.0,
-        height: size.1,
-    }
+fn initialize_level(size: Size) -> Grid {
+    let last = Position {
+        x: size.width as i32 - 1,
+        y: size.height as i32 - 1,
+    };
+    Grid::from_fn(size, |Position { x, y }| {
+        if x == 0 || y == 0 || x == last.x || y == last.y {
+            Cell::Wall
+        } else {
+            Cell::Empty
+        }
+    })
 }
 
 struct FoodSpawner {
     time_to_next_spawn: Duration,
     time_since_last_spawn: Duration,
+    rng: StdRng,
 }
 
 impl FoodSpawner {
@@ -175,12 +211,11 @@ impl FoodSpawner {
         if self.time_since_last_spawn > self.time_to_next_spawn {
             self.time_since_last_spawn = Duration::new(0, 0);
 
-            let mut rng = rand::thread_rng();
-            self.time_to_next_spawn = Duration::new(rng.gen_range(5..10), 0);
+            self.time_to_next_spawn = Duration::new(self.rng.gen_range(5..10), 0);
 
             return Some(Position {
-                x: rng.gen_range(1..grid.width - 1) as i32,
-                y: rng.gen_range(1..grid.height - 1) as i32,
+                x: self.rng.gen_range(1..grid.width() - 1) as i32,
+                y: self.rng.gen_range(1..grid.height() - 1) as i32,
             });
         }
 
@@ -195,15 +230,21 @@ pub enum UpdateResult {
 }
 
 impl GameState {
-    pub fn new() -> Self {
-        let default_size = (60, 30);
+    /// A new game, where the same seed places the food at the same places.
+    pub fn new(grid_size: Size, seed: Option<u64>, difficulty: Difficulty) -> Self {
+        let speed = match difficulty {
+            Difficulty::Easy => 0.7,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.5,
+        };
 
         Self {
-            snake: Snake::new(Position { x: 3, y: 1 }, 3),
-            grid: initialize_level(default_size),
+            snake: Snake::new(Position { x: 3, y: 1 }, 3, speed),
+            grid: initialize_level(grid_size),
             food_spawmer: FoodSpawner {
                 time_to_next_spawn: Duration::new(0, 0),
                 time_since_last_spawn: Duration::new(0, 0),
+                rng: seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64),
             },
             foods: vec![],
         }
@@ -236,25 +277,30 @@ impl GameState {
         UpdateResult::Ok
     }
 
-    pub fn render(&self) {
+    pub fn render(&self, canvas: &mut Canvas, viewport: &Viewport, settings: &Settings) {
         // Render the level.
         let mut render = self.grid.clone();
 
         // Render the snake.
         for part in self.snake.parts.iter() {
-            render.set_cell(*part, Cell::Snake(SnakePart::Body));
+            render.set(*part, Cell::Snake(SnakePart::Body));
         }
-        render.set_cell(
+        render.set(
             *self.snake.parts.front().unwrap(),
             Cell::Snake(SnakePart::Head),
         );
 
         for food in &self.foods {
-            render.set_cell(*food, Cell::Food);
+            render.set(*food, Cell::Food);
         }
 
-        // Print all.
-        render.print();
+        // Draw all.
+        let theme = &settings.theme;
+        let walls = settings.common.walls;
+        settings
+            .common
+            .tileset
+            .draw_grid(canvas, &render, viewport, walls, |cell| theme.style(*cell));
     }
 
     pub fn set_direction(&mut self, new_direction: Direction) {
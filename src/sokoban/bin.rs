@@ -1,21 +1,44 @@
-mod flows;
-mod level;
-mod sokoban;
+use std::{env, process::ExitCode};
 
-use flows::SokobanLauncher;
 use tiny::app::TinyApp;
-use tiny::flow::IntroFlow;
+use tiny::cli::{Args, Cli, Flag};
+use tiny::config::Config;
+use tiny::flow::{GameLauncher, IntroFlow};
+use tiny::sokoban::flows::SokobanLauncher;
 
-fn main() {
-    let controls = [
-        ("wasd", "move"),
-        ("r", "reset"),
-        ("u", "undo"),
-        ("q", "quit"),
-    ];
+/// Sets up the app from the command line and the user's settings,
+/// so that mistakes are reported before the terminal is set up.
+fn load_app(args: &Args) -> Result<TinyApp, String> {
+    let mut config = Config::load("sokoban")?;
+    args.apply_to(&mut config)?;
+    let launcher = SokobanLauncher::from_config(&config)?;
 
-    let mut app = TinyApp::new()
-        .with_flow(IntroFlow::<SokobanLauncher>::new("Sokoban").with_controls(&controls));
+    let app = TinyApp::new()
+        .with_target_fps(launcher.target_fps())
+        .with_pause_keys(launcher.pause_keys())
+        .with_args(args)?;
+    if args.no_intro {
+        let game = launcher
+            .launch_game(&launcher.default_context())
+            .map_err(|error| error.to_string())?;
+        return Ok(app.with_boxed_flow(game));
+    }
 
-    app.run();
+    Ok(app.with_flow(IntroFlow::new("Sokoban", launcher)))
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::new("sokoban", env!("CARGO_PKG_VERSION"))
+        .with_about("Push every box onto a target.")
+        .with_flags(&[
+            Flag::Level,
+            Flag::LevelFile,
+            Flag::SaveSlot,
+            Flag::Fps,
+            Flag::NoColor,
+            Flag::NoIntro,
+            Flag::Replay,
+        ]);
+
+    cli.run(env::args().skip(1), load_app)
 }
@@ -1,11 +1,21 @@
-use crate::{
-    level::{Grid, LEVELS},
+use super::{
+    level::{player_can_move, Grid},
+    progress,
+    settings::Settings,
     sokoban::*,
 };
-use tiny::{
+use std::{any::Any, rc::Rc};
+
+use crate::{
     app::Time,
-    flow::{GameLauncher, QuitFlow},
+    banner::Font,
+    config::Config,
+    flow::{
+        FlowError, FlowResult, GameLauncher, LaunchContext, LaunchOption, MenuFlow, Transition,
+    },
+    input::InputMap,
     prelude::*,
+    viewport::Viewport,
 };
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -14,80 +24,205 @@ pub enum Command {
     RestartLevel,
     Quit,
     Undo,
-    Unknown,
+    Redo,
+    Menu,
 }
 
-pub fn translate_input(input: Key) -> Command {
-    match input {
-        Key::W => Command::Move(0, -1),
-        Key::A => Command::Move(-1, 0),
-        Key::S => Command::Move(0, 1),
-        Key::D => Command::Move(1, 0),
-        Key::R => Command::RestartLevel,
-        Key::Q => Command::Quit,
-        Key::U => Command::Undo,
-        _ => Command::Unknown,
-    }
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum MenuAction {
+    Resume,
+    Restart,
+    LevelSelect,
+    Level(usize),
+    Quit,
 }
 
-pub struct SokobanLauncher;
+pub fn input_map() -> InputMap<Command> {
+    let ctrl = |key| KeyEvent::new(key, Modifiers::CTRL);
+    InputMap::new()
+        .bind("up", "move", [Key::W, Key::Up], Command::Move(0, -1))
+        .bind("left", "move", [Key::A, Key::Left], Command::Move(-1, 0))
+        .bind("down", "move", [Key::S, Key::Down], Command::Move(0, 1))
+        .bind("right", "move", [Key::D, Key::Right], Command::Move(1, 0))
+        .bind("reset", "reset", [Key::R], Command::RestartLevel)
+        .bind("undo", "undo", [Key::U.into(), ctrl(Key::Z)], Command::Undo)
+        // Not Ctrl+Shift+Z, terminals send the same byte for it as for Ctrl+Z.
+        .bind(
+            "redo",
+            "redo",
+            [KeyEvent::new(Key::U, Modifiers::SHIFT), ctrl(Key::Y)],
+            Command::Redo,
+        )
+        .bind("menu", "menu", [Key::Escape, Key::M], Command::Menu)
+        .bind("quit", "quit", [Key::Q], Command::Quit)
+}
+
+pub struct SokobanLauncher {
+    settings: Rc<Settings>,
+}
 
 impl GameLauncher for SokobanLauncher {
-    fn new() -> Self {
-        SokobanLauncher
+    fn from_config(config: &Config) -> Result<Self, String> {
+        Ok(SokobanLauncher {
+            settings: Rc::new(Settings::from_config(config)?),
+        })
+    }
+
+    fn name(&self) -> &str {
+        "Sokoban"
+    }
+
+    fn controls(&self) -> Vec<(String, String)> {
+        self.settings.common.input_map.controls()
+    }
+
+    fn target_fps(&self) -> u32 {
+        self.settings.common.fps
+    }
+
+    fn font(&self) -> Font {
+        self.settings.common.font.clone()
+    }
+
+    fn default_context(&self) -> LaunchContext {
+        let saved_level = || self.settings.save_slot.and_then(progress::load);
+        LaunchContext {
+            level: self
+                .settings
+                .start_level
+                .or_else(saved_level)
+                .filter(|level| *level < self.settings.levels.len()),
+            save_slot: self.settings.save_slot,
+            ..LaunchContext::default()
+        }
+    }
+
+    fn launch_options(&self) -> Vec<LaunchOption> {
+        vec![LaunchOption::Level {
+            count: self.settings.levels.len(),
+        }]
     }
 
-    fn launch_game(&self) -> Box<dyn Flow> {
-        Box::new(GameFlow::new(0).expect("Failed to initialize game!"))
+    fn launch_game(&self, context: &LaunchContext) -> Result<Box<dyn Flow>, FlowError> {
+        let mut settings = (*self.settings).clone();
+        settings.save_slot = context.save_slot;
+
+        let level_index = context.level.unwrap_or(0);
+        Ok(Box::new(GameFlow::new(level_index, Rc::new(settings))?))
     }
 }
 
 pub struct GameFlow {
+    /// The level with the player and the boxes where they are, rebuilt on every change of the game state.
     current_grid: Grid,
     game_state: GameState,
     level_index: usize,
+    settings: Rc<Settings>,
+    /// Scrolls levels larger than the terminal to follow the player.
+    viewport: Viewport,
+    /// Why the progress couldn't be saved, the game goes on without it.
+    save_error: Option<String>,
 }
 
-struct EndFlow {}
+/// Shown once the last level is completed.
+struct EndFlow {
+    settings: Rc<Settings>,
+}
 
 impl Flow for EndFlow {
-    fn render(&self) {
-        print!("{END}");
+    fn render(&self, canvas: &mut Canvas) {
+        let text_style = Style::new();
+        let title_style = Style::new().fg(Color::BrightYellow).bold();
+        let mut lines: Vec<(String, Style)> = self
+            .settings
+            .common
+            .font
+            .render_to_fit("THE END", canvas.width())
+            .into_iter()
+            .map(|line| (line, title_style))
+            .collect();
+        lines.extend(
+            [
+                "",
+                "All levels completed.",
+                "",
+                "Congratulations!",
+                "",
+                "Any key to quit...",
+            ]
+            .map(|line| (line.to_string(), text_style)),
+        );
+
+        let top = (canvas.height() as i32 - lines.len() as i32) / 2;
+        for (y, (line, style)) in lines.iter().enumerate() {
+            canvas.put_centered_str(top + y as i32, line, *style);
+        }
     }
 
-    fn handle_key(&mut self, _key: Key) -> Option<Box<dyn Flow>> {
-        Some(Box::new(QuitFlow {}))
+    fn handle_key(&mut self, _key: KeyEvent) -> FlowResult {
+        Ok(Transition::Pop)
     }
 }
 
 impl GameFlow {
-    fn new(level_index: usize) -> Result<Self, String> {
-        let game_state =
-            GameState::load_level(LEVELS.get(level_index).ok_or("Invalid level index")?)?;
+    fn new(level_index: usize, settings: Rc<Settings>) -> Result<Self, String> {
+        let level = settings
+            .levels
+            .get(level_index)
+            .ok_or("Invalid level index")?;
+        let game_state = GameState::load_level(level)?;
         let initial_grid = game_state.render_grid();
 
+        let save_error = settings
+            .save_slot
+            .and_then(|slot| progress::save(slot, level_index).err());
+
         Ok(GameFlow {
             game_state,
+            viewport: Viewport::new(initial_grid.size()),
             current_grid: initial_grid,
             level_index,
+            settings,
+            save_error,
         })
     }
 }
 
 impl Flow for GameFlow {
-    fn render(&self) {
-        self.current_grid.print();
+    fn render(&self, canvas: &mut Canvas) {
+        let theme = &self.settings.theme;
+        let tileset = self.settings.common.tileset;
+        let walls = self.settings.common.walls;
+        tileset.draw_grid(canvas, &self.current_grid, &self.viewport, walls, |cell| {
+            theme.style(*cell)
+        });
+        for position in self.game_state.boxes_on_target() {
+            let area = tileset.cell_area(self.viewport.to_screen(position));
+            canvas.set_rect_style(area, theme.box_on_target);
+        }
+
+        if let Some(error) = &self.save_error {
+            let message = format!("Progress not saved: {error}");
+            let y = canvas.height() as i32 - 1;
+            canvas
+                .layer(1)
+                .put_centered_str(y, &message, Style::new().fg(Color::BrightRed));
+        }
     }
 
-    fn handle_key(&mut self, key: Key) -> Option<Box<dyn Flow>> {
-        let command = translate_input(key);
+    fn handle_key(&mut self, key: KeyEvent) -> FlowResult {
+        let Some(command) = self.settings.common.input_map.get(key) else {
+            return Ok(Transition::None);
+        };
+
         match command {
             Command::Move(dx, dy) => {
                 let direction = Direction { x: dx, y: dy };
-                if self
-                    .current_grid
-                    .player_can_move(self.game_state.player_position, direction)
-                {
+                if player_can_move(
+                    &self.current_grid,
+                    self.game_state.player_position,
+                    direction,
+                ) {
                     self.game_state.move_player(&self.current_grid, direction);
                 }
             }
@@ -95,30 +230,75 @@ impl Flow for GameFlow {
                 self.game_state.reset();
             }
             Command::Quit => {
-                return Some(Box::new(QuitFlow {}));
+                return Ok(Transition::Pop);
             }
             Command::Undo => {
                 self.game_state.undo();
             }
-            _ => {}
+            Command::Redo => {
+                self.game_state.redo();
+            }
+            Command::Menu => {
+                return Ok(Transition::Push(Box::new(
+                    MenuFlow::new("Paused")
+                        .with_option("Resume", MenuAction::Resume)
+                        .with_option("Restart", MenuAction::Restart)
+                        .with_option("Level select", MenuAction::LevelSelect)
+                        .with_option("Quit", MenuAction::Quit),
+                )));
+            }
         }
 
-        None
+        self.current_grid = self.game_state.render_grid();
+        Ok(Transition::None)
     }
 
-    fn update(&mut self, _time: &Time) -> Option<Box<dyn Flow>> {
-        self.current_grid = self.game_state.render_grid();
+    fn handle_result(&mut self, result: Box<dyn Any>) -> FlowResult {
+        let Some(action) = result.downcast_ref::<MenuAction>() else {
+            return Ok(Transition::None);
+        };
+
+        Ok(match action {
+            MenuAction::Resume => Transition::None,
+            MenuAction::Restart => {
+                self.game_state.reset();
+                self.current_grid = self.game_state.render_grid();
+                Transition::None
+            }
+            MenuAction::LevelSelect => {
+                let menu = (0..self.settings.levels.len()).fold(
+                    MenuFlow::new("Select level"),
+                    |menu, index| {
+                        menu.with_option(&format!("Level {}", index + 1), MenuAction::Level(index))
+                    },
+                );
+                Transition::Push(Box::new(menu))
+            }
+            MenuAction::Level(index) => {
+                Transition::Replace(Box::new(GameFlow::new(*index, self.settings.clone())?))
+            }
+            MenuAction::Quit => Transition::Pop,
+        })
+    }
+
+    fn update(&mut self, time: &Time) -> FlowResult {
+        self.viewport
+            .resize(self.settings.common.tileset.cells_in(time.viewport_size));
+        self.viewport.follow(self.game_state.player_position);
         if !self.game_state.level_is_complete() {
-            return None;
+            return Ok(Transition::None);
         }
 
         // Load next level if any.
-        if self.level_index + 1 < LEVELS.len() {
-            Some(Box::new(
-                GameFlow::new(self.level_index + 1).expect("Failed to load level."),
-            ))
+        if self.level_index + 1 < self.settings.levels.len() {
+            Ok(Transition::Replace(Box::new(GameFlow::new(
+                self.level_index + 1,
+                self.settings.clone(),
+            )?)))
         } else {
-            Some(Box::new(EndFlow {}))
+            Ok(Transition::Replace(Box::new(EndFlow {
+                settings: self.settings.clone(),
+            })))
         }
     }
 }
@@ -1,6 +1,10 @@
 use std::collections::HashMap;
 
-use tiny::math::{Direction, Position};
+use crate::{
+    math::{Direction, Position},
+    style::{Color, Style},
+    tileset::{Tile, Tileset},
+};
 
 const LEVEL_0: &str = "####
 @.QX
@@ -31,14 +35,68 @@ pub enum Cell {
     Target,
 }
 
-impl From<Cell> for char {
-    fn from(cell: Cell) -> char {
+impl Tile for Cell {
+    fn glyph(&self, tileset: Tileset) -> &str {
+        match tileset {
+            Tileset::Ascii => match self {
+                Cell::Wall => "#",
+                Cell::Empty => " ",
+                Cell::Player => "@",
+                Cell::Box(_) => "Q",
+                Cell::Target => "X",
+            },
+            Tileset::Unicode => match self {
+                Cell::Wall => "█",
+                Cell::Empty => " ",
+                Cell::Player => "☺",
+                Cell::Box(_) => "▣",
+                Cell::Target => "○",
+            },
+            Tileset::Wide => match self {
+                Cell::Wall => "██",
+                Cell::Empty => "  ",
+                Cell::Player => "🙂",
+                Cell::Box(_) => "📦",
+                Cell::Target => "⭕",
+            },
+        }
+    }
+
+    fn is_wall(&self) -> bool {
+        *self == Cell::Wall
+    }
+}
+
+/// Styles of the cells, their colours can be set in the `[colors]` table of the config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub wall: Style,
+    pub player: Style,
+    pub box_: Style,
+    pub target: Style,
+    pub box_on_target: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            wall: Style::new().fg(Color::BrightBlack),
+            player: Style::new().fg(Color::BrightYellow).bold(),
+            box_: Style::new().fg(Color::Yellow),
+            target: Style::new().fg(Color::Red),
+            box_on_target: Style::new().fg(Color::BrightGreen).bold(),
+        }
+    }
+}
+
+impl Theme {
+    pub fn style(&self, cell: Cell) -> Style {
         match cell {
-            Cell::Wall => '#',
-            Cell::Empty => ' ',
-            Cell::Player => '@',
-            Cell::Box(_) => 'Q',
-            Cell::Target => 'X',
+            Cell::Wall => self.wall,
+            Cell::Empty => Style::new(),
+            Cell::Player => self.player,
+            Cell::Box(_) => self.box_,
+            Cell::Target => self.target,
         }
     }
 }
@@ -66,115 +124,54 @@ pub struct Level {
     pub box_positions: HashMap<i32, Position>,
 }
 
-#[derive(Debug, Clone)]
-pub struct Grid {
-    grid: Vec<Cell>,
-    width: usize,
-    height: usize,
-}
+/// Board of a level, where the player and the boxes are only drawn by `GameState::render_grid`.
+pub type Grid = crate::grid::Grid<Cell>;
 
-impl Grid {
-    pub fn cell_at(&self, position: Position) -> Cell {
-        self.grid[position.x as usize + self.width * position.y as usize]
+impl Cell {
+    /// Whether the player or a box can move onto the cell.
+    pub fn is_free(self) -> bool {
+        self == Cell::Empty || self == Cell::Target
     }
+}
 
-    pub fn set_cell(&mut self, position: Position, value: Cell) {
-        self.grid[position.x as usize + self.width * position.y as usize] = value;
-    }
-
-    pub fn is_empty(&self, position: Position) -> bool {
-        let cell = self.cell_at(position);
-        cell == Cell::Empty || cell == Cell::Target
-    }
-
-    pub fn player_can_move(&self, from_position: Position, direction: Direction) -> bool {
-        let to_position = from_position + direction;
-
-        if to_position.x < 0
-            || to_position.x > self.width as i32
-            || to_position.y < 0
-            || to_position.y > self.height as i32
-        {
-            return false;
-        }
-
-        if self.is_empty(to_position) {
-            return true;
-        }
+fn is_free(grid: &Grid, position: Position) -> bool {
+    grid.get(position).is_some_and(|cell| cell.is_free())
+}
 
-        let next_position = to_position + direction;
-        match self.cell_at(to_position) {
-            Cell::Box(_) => self.is_empty(next_position),
-            _ => false,
-        }
+pub fn player_can_move(grid: &Grid, from_position: Position, direction: Direction) -> bool {
+    let to_position = from_position + direction;
+    if is_free(grid, to_position) {
+        return true;
     }
 
-    pub fn print(&self) {
-        for line in self.grid.chunks(self.width as usize) {
-            let line_string = line
-                .iter()
-                .map(|cell| <Cell as Into<char>>::into(*cell))
-                .collect::<String>();
-
-            println!("{line_string}");
-        }
+    let next_position = to_position + direction;
+    match grid.get(to_position) {
+        Some(Cell::Box(_)) => is_free(grid, next_position),
+        _ => false,
     }
 }
 
 pub fn parse_level(level_string: &str) -> Result<Level, String> {
-    let mut lines: Vec<Vec<Cell>> = level_string
-        .split('\n')
-        .map(|line| {
-            line.chars()
-                .filter_map(|char| char.try_into().ok())
-                .collect()
-        })
-        .collect();
-
-    let width = lines
-        .iter()
-        .max_by_key(|line| line.len())
-        .ok_or("Malformated level")?
-        .len();
-
-    let height = lines.len();
-
-    for line in &mut lines {
-        line.resize(width, Cell::Empty);
-    }
-
-    let mut grid: Vec<Cell> = lines.into_iter().flatten().collect();
+    let mut grid = Grid::parse(level_string, Cell::Empty, |c| Cell::try_from(c).ok())?;
 
     // Find the player start position.
-    let start_index = grid
+    let start_position = grid
         .iter()
-        .position(|&cell| cell == Cell::Player)
+        .find(|(_, &cell)| cell == Cell::Player)
+        .map(|(position, _)| position)
         .ok_or_else(|| "Level is missing a player position.".to_string())?;
 
-    let start_position = Position {
-        x: (start_index % width) as i32,
-        y: (start_index / width) as i32,
-    };
-
-    // Find the indices and positions of the loads.
-    let load_indices: Vec<usize> = grid
-        .iter()
-        .enumerate()
-        .filter(|&(_, &cell)| matches!(cell, Cell::Box(_)))
-        .map(|(index, _)| index)
-        .collect();
-    let load_positions: Vec<Position> = load_indices
+    // Find the positions of the loads.
+    let load_positions: Vec<Position> = grid
         .iter()
-        .map(|index| Position {
-            x: (index % width) as i32,
-            y: (index / width) as i32,
-        })
+        .filter(|(_, cell)| matches!(cell, Cell::Box(_)))
+        .map(|(position, _)| position)
         .collect();
 
     // Set the cells where the player and loads are as empty, they are managed as part of the game state.
-    grid[start_index] = Cell::Empty;
-    for index in load_indices {
-        grid[index] = Cell::Empty;
+    grid.set(start_position, Cell::Empty);
+    for position in &load_positions {
+        grid.set(*position, Cell::Empty);
     }
 
     // Give an id to the loads.
@@ -184,11 +181,7 @@ pub fn parse_level(level_string: &str) -> Result<Level, String> {
     }
 
     Ok(Level {
-        grid: Grid {
-            grid,
-            width,
-            height,
-        },
+        grid,
         start_position,
         box_positions: load_hashmap,
     })
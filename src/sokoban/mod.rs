@@ -0,0 +1,8 @@
+//! Sokoban, played on its own by the `sokoban` binary or from the `tiny` launcher.
+
+pub mod flows;
+mod level;
+mod progress;
+mod settings;
+#[allow(clippy::module_inception)]
+mod sokoban;
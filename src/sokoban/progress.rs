@@ -0,0 +1,23 @@
+use std::{fs, path::PathBuf};
+
+use crate::config::data_dir;
+
+fn path(slot: usize) -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join(format!("sokoban-{slot}.save")))
+}
+
+/// Index of the level reached in a save slot, `None` if nothing was saved.
+pub fn load(slot: usize) -> Option<usize> {
+    fs::read_to_string(path(slot)?).ok()?.trim().parse().ok()
+}
+
+pub fn save(slot: usize, level_index: usize) -> Result<(), String> {
+    let path = path(slot).ok_or("No directory to save the progress to")?;
+    let write = |path: &PathBuf| {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, format!("{level_index}\n"))
+    };
+    write(&path).map_err(|error| format!("{}: {}", path.display(), error))
+}
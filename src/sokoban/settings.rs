@@ -0,0 +1,104 @@
+use std::fs;
+
+use crate::{
+    app::DEFAULT_TARGET_FPS,
+    config::{CommonSettings, Config},
+};
+
+use super::{
+    flows::{input_map, Command},
+    level::{parse_level, Theme, LEVELS},
+};
+
+/// The settings of the config file next to the common ones.
+const KNOWN_SETTINGS: [&str; 8] = [
+    "level",
+    "level_file",
+    "save_slot",
+    "colors.wall",
+    "colors.player",
+    "colors.box",
+    "colors.target",
+    "colors.box_on_target",
+];
+
+/// Game settings, the defaults changed by the user's config.
+#[derive(Clone)]
+pub struct Settings {
+    pub common: CommonSettings<Command>,
+    pub theme: Theme,
+    pub levels: Vec<String>,
+    /// Index of the first level played, the saved one or the first when not set.
+    pub start_level: Option<usize>,
+    /// Slot the progress is saved to.
+    pub save_slot: Option<usize>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            common: CommonSettings::new(DEFAULT_TARGET_FPS, input_map()),
+            theme: Theme::default(),
+            levels: LEVELS.iter().map(|level| level.to_string()).collect(),
+            start_level: None,
+            save_slot: None,
+        }
+    }
+}
+
+impl Settings {
+    pub fn from_config(config: &Config) -> Result<Self, String> {
+        let mut settings = Settings::default();
+        settings.common.read(config, &KNOWN_SETTINGS)?;
+
+        let theme = &mut settings.theme;
+        theme.wall = config.get_foreground("colors.wall", theme.wall)?;
+        theme.player = config.get_foreground("colors.player", theme.player)?;
+        theme.box_ = config.get_foreground("colors.box", theme.box_)?;
+        theme.target = config.get_foreground("colors.target", theme.target)?;
+        theme.box_on_target = config.get_foreground("colors.box_on_target", theme.box_on_target)?;
+
+        if let Some(path) = config.get_str("level_file")? {
+            settings.levels = read_levels(path)?;
+        }
+
+        // Levels are numbered from 1 for the player.
+        let level_count = settings.levels.len() as i64;
+        if let Some(level) = config.get_integer("level", 1..=level_count)? {
+            settings.start_level = Some(level as usize - 1);
+        }
+        if let Some(slot) = config.get_integer("save_slot", 1..=9)? {
+            settings.save_slot = Some(slot as usize);
+        }
+
+        Ok(settings)
+    }
+}
+
+/// Reads the levels of a file, separated by blank lines, `;` starts a comment line.
+fn read_levels(path: &str) -> Result<Vec<String>, String> {
+    let text = fs::read_to_string(path).map_err(|error| format!("{path}: {error}"))?;
+
+    let mut levels = vec![];
+    let mut level: Vec<&str> = vec![];
+    for line in text.lines().chain([""]) {
+        if line.trim_start().starts_with(';') {
+            continue;
+        }
+
+        if !line.trim().is_empty() {
+            level.push(line);
+        } else if !level.is_empty() {
+            levels.push(level.join("\n"));
+            level.clear();
+        }
+    }
+
+    for (index, level) in levels.iter().enumerate() {
+        parse_level(level).map_err(|error| format!("{path}: level {}: {error}", index + 1))?;
+    }
+    if levels.is_empty() {
+        return Err(format!("{path}: no levels found"));
+    }
+    Ok(levels)
+}
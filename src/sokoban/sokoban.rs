@@ -1,19 +1,8 @@
 use std::collections::HashMap;
 
-use tiny::math::{Direction, Position};
+use crate::math::{Direction, Position};
 
-use crate::level::{parse_level, Cell, Grid, Level};
-
-pub const END: &str = "#########################
-#                       #
-#       The End!        #
-# All levels completed. #
-#                       #
-#   Congratulations!    #
-#                       #
-#   Any key to quit...  #
-#                       #
-#########################";
+use super::level::{parse_level, player_can_move, Cell, Grid, Level};
 
 struct Move {
     player_move: Direction,
@@ -25,6 +14,7 @@ pub struct GameState {
     box_positions: HashMap<i32, Position>,
     level: Level,
     move_history: Vec<Move>,
+    redo_history: Vec<Move>,
 }
 
 impl GameState {
@@ -34,6 +24,7 @@ impl GameState {
             box_positions: level.box_positions.clone(),
             level,
             move_history: vec![],
+            redo_history: vec![],
         }
     }
 
@@ -48,9 +39,9 @@ impl GameState {
     pub fn render_grid(&self) -> Grid {
         let mut new_grid = self.level.grid.clone();
 
-        new_grid.set_cell(self.player_position, Cell::Player);
+        new_grid.set(self.player_position, Cell::Player);
         for (load_id, position) in &self.box_positions {
-            new_grid.set_cell(*position, Cell::Box(*load_id));
+            new_grid.set(*position, Cell::Box(*load_id));
         }
 
         new_grid
@@ -76,10 +67,30 @@ impl GameState {
                 panic!("Got an id of an unnexisting box.");
             }
         }
+
+        self.redo_history.push(move_item);
+    }
+
+    pub fn redo(&mut self) {
+        let Some(move_item) = self.redo_history.pop() else {
+            return;
+        };
+
+        self.player_position += move_item.player_move;
+
+        if let Some(box_id) = move_item.box_move {
+            if let Some(load_position) = self.box_positions.get_mut(&box_id) {
+                *load_position += move_item.player_move;
+            } else {
+                panic!("Got an id of an unnexisting box.");
+            }
+        }
+
+        self.move_history.push(move_item);
     }
 
     pub fn move_player(&mut self, grid: &Grid, direction: Direction) {
-        assert!(grid.player_can_move(self.player_position, direction));
+        assert!(player_can_move(grid, self.player_position, direction));
 
         let to_position = self.player_position + direction;
 
@@ -89,7 +100,7 @@ impl GameState {
         };
 
         // Move the load if there is one and it can move.
-        if let Cell::Box(uid) = grid.cell_at(to_position) {
+        if let Some(Cell::Box(uid)) = grid.get(to_position).copied() {
             if let Some(load_position) = self.box_positions.get_mut(&uid) {
                 *load_position = to_position + direction;
                 move_item.box_move = Some(uid);
@@ -100,15 +111,17 @@ impl GameState {
 
         self.player_position = to_position;
         self.move_history.push(move_item);
+        self.redo_history.clear();
     }
 
-    pub fn level_is_complete(&self) -> bool {
-        for load_position in self.box_positions.values() {
-            if self.level.grid.cell_at(*load_position) != Cell::Target {
-                return false;
-            }
-        }
+    pub fn boxes_on_target(&self) -> impl Iterator<Item = Position> + '_ {
+        self.box_positions
+            .values()
+            .copied()
+            .filter(|position| self.level.grid.get(*position) == Some(&Cell::Target))
+    }
 
-        true
+    pub fn level_is_complete(&self) -> bool {
+        self.boxes_on_target().count() == self.box_positions.len()
     }
 }
@@ -1,87 +1,479 @@
-use core::time;
-use std::thread;
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
-use crate::flow::{DefaultFlow, Flow};
-use crate::platform::{Key, Platform};
+use crate::backend::{Backend, Replay, ReplayBackend};
+use crate::canvas::{Canvas, Layers};
+use crate::cli::Args;
+use crate::clock::{Clock, RealClock, VirtualClock};
+use crate::flow::{DefaultFlow, Flow, FlowError, Transition};
+use crate::input::{Event, EventQueue};
+use crate::math::{Direction, Rect, Size};
+use crate::platform::{Key, KeyEvent, Platform};
+use crate::screen::Screen;
+use crate::style::{ColorSupport, Style};
 
 pub struct Time {
     pub frame_delta_time: Duration,
     pub time_since_startup: Duration,
+    /// Size of the terminal the app is drawn to.
+    pub viewport_size: Size,
+}
+
+pub const DEFAULT_TARGET_FPS: u32 = 30;
+const DEFAULT_MAX_CATCH_UP_STEPS: u32 = 5;
+
+/// Why the app is paused, a pause caused by losing the focus ends when the focus comes back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PauseReason {
+    Key,
+    FocusLost,
+}
+
+/// Updates at a constant rate, independently of the frame rate.
+struct FixedTimestep {
+    step: Duration,
+    accumulator: Duration,
 }
 
 pub struct TinyApp {
-    flow: Box<dyn Flow>,
-    platform: Platform,
+    /// Running flows, the last one is active and the ones below are paused.
+    flows: Vec<Box<dyn Flow>>,
+    backend: Option<Box<dyn Backend>>,
+    clock: Box<dyn Clock>,
+    color_support: Option<ColorSupport>,
+    replay: Option<Replay>,
+    time_since_startup: Duration,
+    frame_time_target: Duration,
+    fixed_timestep: Option<FixedTimestep>,
+    /// Most fixed updates run in a single frame.
+    max_catch_up_steps: u32,
+    started: bool,
+    paused: Option<PauseReason>,
+    pause_keys: Vec<KeyEvent>,
+    pause_on_focus_loss: bool,
+    events: EventQueue,
+    screen: Screen,
+    /// Layers of the flow being rendered, composed onto the screen before the next flow renders.
+    layers: Layers,
+    quit_requested: bool,
 }
 
 impl TinyApp {
     pub fn new() -> Self {
         TinyApp {
-            flow: Box::new(DefaultFlow {}),
-            platform: Platform::new(),
+            flows: vec![Box::new(DefaultFlow {})],
+            backend: None,
+            clock: Box::new(RealClock::new()),
+            color_support: None,
+            replay: None,
+            time_since_startup: Duration::ZERO,
+            frame_time_target: Duration::from_secs(1) / DEFAULT_TARGET_FPS,
+            fixed_timestep: None,
+            max_catch_up_steps: DEFAULT_MAX_CATCH_UP_STEPS,
+            started: false,
+            paused: None,
+            pause_keys: vec![],
+            pause_on_focus_loss: true,
+            events: EventQueue::new(),
+            screen: Screen::new(0, 0),
+            layers: Layers::new(Size::default()),
+            quit_requested: false,
         }
     }
 
-    pub fn with_flow<FlowType>(mut self, flow: FlowType) -> Self
+    /// Runs the app on the given backend instead of the terminal.
+    pub fn with_backend<BackendType>(mut self, backend: BackendType) -> Self
+    where
+        BackendType: Backend + 'static,
+    {
+        self.backend = Some(Box::new(backend));
+        self
+    }
+
+    pub fn with_flow<FlowType>(self, flow: FlowType) -> Self
     where
         FlowType: Flow + 'static,
     {
-        self.flow = Box::new(flow);
+        self.with_boxed_flow(Box::new(flow))
+    }
+
+    pub fn with_boxed_flow(mut self, flow: Box<dyn Flow>) -> Self {
+        self.flows = vec![flow];
         self
     }
 
-    pub fn run(&mut self) {
-        const FRAME_TIME_TARGET: time::Duration = time::Duration::from_millis(33);
-        let game_began = Instant::now();
-        let mut last_frame_time = Instant::now().duration_since(game_began);
+    /// Uses the given clock to measure frame times and wait between frames.
+    pub fn with_clock<ClockType>(mut self, clock: ClockType) -> Self
+    where
+        ClockType: Clock + 'static,
+    {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    /// Sets how many frames per second the app renders at most.
+    pub fn with_target_fps(mut self, fps: u32) -> Self {
+        self.frame_time_target = Duration::from_secs(1) / fps.max(1);
+        self
+    }
+
+    /// Applies the command-line flags handled by the app, `--no-color` and `--replay`.
+    pub fn with_args(mut self, args: &Args) -> Result<Self, String> {
+        if args.no_color {
+            self = self.with_color_support(ColorSupport::NoColor);
+        }
+        if let Some(path) = &args.replay {
+            self = self.with_replay(Replay::load(path)?);
+        }
+        Ok(self)
+    }
+
+    /// Presses the keys of a replay on top of the backend's input.
+    ///
+    /// The app switches to a paced [`VirtualClock`], every frame lasting exactly the target frame
+    /// time, so that a replay plays the same game each time as long as the game is seeded.
+    pub fn with_replay(mut self, replay: Replay) -> Self {
+        self.replay = Some(replay);
+        self.clock = Box::new(VirtualClock::paced());
+        self
+    }
+
+    /// Calls `update` with a constant `step` delta time, as many times as needed to catch up with the elapsed time.
+    /// Flows are rendered once per frame with the interpolation alpha between the last two updates.
+    pub fn with_fixed_timestep(mut self, step: Duration) -> Self {
+        self.fixed_timestep = Some(FixedTimestep {
+            step,
+            accumulator: Duration::ZERO,
+        });
+        self
+    }
+
+    /// Caps the number of fixed updates per frame, time beyond that is dropped so a slow frame doesn't snowball.
+    /// Only used with [`TinyApp::with_fixed_timestep`], in either order.
+    pub fn with_max_catch_up_steps(mut self, max_steps: u32) -> Self {
+        self.max_catch_up_steps = max_steps.max(1);
+        self
+    }
+
+    /// Keys toggling the pause. While paused the flows are not updated and the time doesn't advance.
+    pub fn with_pause_keys<K: Into<KeyEvent>>(mut self, keys: impl IntoIterator<Item = K>) -> Self {
+        self.pause_keys = keys.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Whether the app pauses when the terminal loses the focus, on by default.
+    pub fn with_pause_on_focus_loss(mut self, enabled: bool) -> Self {
+        self.pause_on_focus_loss = enabled;
+        self
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.is_some()
+    }
+
+    /// Overrides the colour support detected from the environment.
+    pub fn with_color_support(mut self, color_support: ColorSupport) -> Self {
+        self.color_support = Some(color_support);
+        self
+    }
+
+    /// Runs the app until it quits, the terminal is restored before returning.
+    pub fn run(&mut self) -> Result<(), FlowError> {
+        let result = self.run_loop();
+
+        // Drop the backend now, so that errors can be reported on a restored terminal.
+        self.backend = None;
+        self.started = false;
+
+        result
+    }
+
+    fn run_loop(&mut self) -> Result<(), FlowError> {
+        let mut last_frame_time = self.clock.now();
 
         while !self.should_quit() {
-            let time_now = Instant::now().duration_since(game_began);
+            let time_now = self.clock.now();
             let elapsed_time = time_now - last_frame_time;
             last_frame_time = time_now;
 
-            if let Some(input_char) = self.platform.poll_input() {
-                let key = Platform::translate_input(input_char);
-                self.handle_key(key);
+            self.step(elapsed_time)?;
+
+            let frame_duration = self.clock.now() - time_now;
+            let frame_time_target = self.frame_time_target();
+            if frame_duration < frame_time_target {
+                self.clock.sleep(frame_time_target - frame_duration);
             }
+        }
 
-            self.update(&Time {
-                frame_delta_time: elapsed_time,
-                time_since_startup: time_now,
-            });
+        Ok(())
+    }
+
+    /// Runs a single frame as if `delta_time` elapsed since the previous one.
+    pub fn step(&mut self, delta_time: Duration) -> Result<(), FlowError> {
+        self.start()?;
+
+        self.process_input()?;
+
+        let alpha = if self.is_paused() {
+            self.fixed_timestep.as_ref().map_or(1.0, |fixed_timestep| {
+                fixed_timestep.accumulator.as_secs_f32() / fixed_timestep.step.as_secs_f32()
+            })
+        } else {
+            self.advance_time(delta_time)?
+        };
 
-            Platform::clear_display();
-            self.render();
+        self.render(alpha);
 
-            if elapsed_time < FRAME_TIME_TARGET {
-                thread::sleep(FRAME_TIME_TARGET - elapsed_time);
+        Ok(())
+    }
+
+    /// Updates the flows for `delta_time` and returns the interpolation alpha to render with.
+    fn advance_time(&mut self, delta_time: Duration) -> Result<f32, FlowError> {
+        match &mut self.fixed_timestep {
+            None => {
+                self.time_since_startup += delta_time;
+                let time = Time {
+                    frame_delta_time: delta_time,
+                    time_since_startup: self.time_since_startup,
+                    viewport_size: self.screen.size(),
+                };
+                self.update(&time)?;
+                Ok(1.0)
             }
+            Some(fixed_timestep) => {
+                fixed_timestep.accumulator += delta_time;
+
+                let step = fixed_timestep.step;
+                let mut steps = 0;
+                while fixed_timestep.accumulator >= step {
+                    if steps == self.max_catch_up_steps {
+                        fixed_timestep.accumulator = Duration::ZERO;
+                        break;
+                    }
+                    fixed_timestep.accumulator -= step;
+                    steps += 1;
+                }
+                let alpha = fixed_timestep.accumulator.as_secs_f32() / step.as_secs_f32();
+
+                for _ in 0..steps {
+                    self.time_since_startup += step;
+                    let time = Time {
+                        frame_delta_time: step,
+                        time_since_startup: self.time_since_startup,
+                        viewport_size: self.screen.size(),
+                    };
+                    self.update(&time)?;
+                }
+                Ok(alpha)
+            }
+        }
+    }
+
+    /// Whether the app is done, either a flow quit or the backend closed.
+    pub fn should_quit(&self) -> bool {
+        self.quit_requested
+            || self.flows.is_empty()
+            || self
+                .backend
+                .as_ref()
+                .is_some_and(|backend| backend.is_closed())
+    }
+
+    fn start(&mut self) -> Result<(), FlowError> {
+        if self.started {
+            return Ok(());
+        }
+
+        // The terminal is only put in raw mode once the app starts running.
+        if self.backend.is_none() {
+            let mut platform = Platform::new().map_err(|error| {
+                FlowError::new(format!("Failed to set up the terminal: {error}"))
+            })?;
+            if let Some(color_support) = self.color_support {
+                platform = platform.with_color_support(color_support);
+            }
+            self.backend = Some(Box::new(platform));
+        }
+        if let Some(replay) = self.replay.take() {
+            let backend = self.backend.take().unwrap();
+            self.backend = Some(Box::new(ReplayBackend::new(backend, replay)));
+        }
+
+        let size = self.backend().size();
+        self.screen = Screen::new(size.width, size.height);
+        self.layers = Layers::new(size);
+        self.started = true;
+
+        Ok(())
+    }
+
+    fn backend(&mut self) -> &mut dyn Backend {
+        self.backend
+            .as_deref_mut()
+            .expect("The backend is created when the app starts running.")
+    }
+
+    fn render(&mut self, alpha: f32) {
+        self.screen.clear();
+
+        // Draw the top flow and all the flows visible through overlays above them,
+        // each overlay covers the layers of the flows below it.
+        let first_visible = self
+            .flows
+            .iter()
+            .rposition(|flow| !flow.is_overlay())
+            .unwrap_or(0);
+        for flow in &self.flows[first_visible..] {
+            self.layers.clear();
+            flow.render_interpolated(&mut Canvas::new(&mut self.layers), alpha);
+            self.layers.compose_onto(&mut self.screen);
+        }
+
+        if self.is_paused() {
+            self.layers.clear();
+            let mut canvas = Canvas::new(&mut self.layers);
+            let style = Style::new().reverse().bold();
+            let rect = Rect::centered(canvas.size(), Size::new(10, 3));
+            canvas.fill_rect(rect, ' ', style);
+            canvas.put_styled_str(rect.origin + Direction { x: 0, y: 1 }, "  PAUSED  ", style);
+            self.layers.compose_onto(&mut self.screen);
+        }
+
+        if let Some(backend) = self.backend.as_deref_mut() {
+            backend.present(&self.screen);
+        }
+    }
+
+    fn process_input(&mut self) -> Result<(), FlowError> {
+        while let Some(event) = self.backend().poll_event() {
+            self.events.push(event);
+        }
+
+        let Some(flow) = self.flows.last() else {
+            return Ok(());
+        };
+
+        for event in self.events.drain(flow.input_policy()) {
+            if self.should_quit() {
+                break;
+            }
+
+            match event {
+                Event::Key(key) => self.handle_key(key)?,
+                Event::Resize(size) => self.handle_resize(size)?,
+                Event::FocusLost => {
+                    if self.pause_on_focus_loss && !self.is_paused() && self.can_pause() {
+                        self.paused = Some(PauseReason::FocusLost);
+                    }
+                }
+                Event::FocusGained => {
+                    if self.paused == Some(PauseReason::FocusLost) {
+                        self.paused = None;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Time between frames, set by the highest flow with a frame rate or by the app.
+    fn frame_time_target(&self) -> Duration {
+        match self.flows.iter().rev().find_map(|flow| flow.target_fps()) {
+            Some(fps) => Duration::from_secs(1) / fps.max(1),
+            None => self.frame_time_target,
+        }
+    }
+
+    /// Whether a key toggles the pause, according to the highest flow with pause keys or to the app.
+    fn is_pause_key(&self, key: KeyEvent) -> bool {
+        match self.flows.iter().rev().find_map(|flow| flow.pause_keys()) {
+            Some(keys) => keys.contains(&key),
+            None => self.pause_keys.contains(&key),
         }
     }
 
-    fn render(&self) {
-        self.flow.render();
+    fn can_pause(&self) -> bool {
+        self.flows.last().is_some_and(|flow| flow.can_pause())
+    }
+
+    fn handle_resize(&mut self, size: Size) -> Result<(), FlowError> {
+        self.screen = Screen::new(size.width, size.height);
+        self.layers = Layers::new(size);
+
+        let mut transition = Transition::None;
+        for flow in &mut self.flows {
+            transition = flow.handle_resize(size)?;
+        }
+        self.apply(transition)
     }
 
-    fn handle_key(&mut self, key: Key) {
-        if let Some(new_flow) = self.flow.handle_key(key) {
-            self.flow = new_flow;
+    fn handle_key(&mut self, key: KeyEvent) -> Result<(), FlowError> {
+        // Signals are disabled in raw mode, Ctrl+C is handled here so that any app can be interrupted.
+        if key.is_ctrl(Key::C) {
+            self.quit_requested = true;
+            return Ok(());
+        }
+
+        if self.is_pause_key(key) && (self.is_paused() || self.can_pause()) {
+            self.paused = match self.paused {
+                Some(_) => None,
+                None => Some(PauseReason::Key),
+            };
+            return Ok(());
+        }
+
+        // Paused flows don't get any input.
+        if self.is_paused() {
+            return Ok(());
+        }
+
+        if let Some(flow) = self.flows.last_mut() {
+            let transition = flow.handle_key(key)?;
+            self.apply(transition)?;
         }
+
+        Ok(())
     }
 
-    fn update(&mut self, time: &Time) {
+    fn update(&mut self, time: &Time) -> Result<(), FlowError> {
         if self.should_quit() {
-            return;
+            return Ok(());
         }
 
-        if let Some(new_flow) = self.flow.update(time) {
-            self.flow = new_flow;
+        if let Some(flow) = self.flows.last_mut() {
+            let transition = flow.update(time)?;
+            self.apply(transition)?;
         }
+
+        Ok(())
     }
 
-    fn should_quit(&self) -> bool {
-        self.flow.should_quit()
+    fn apply(&mut self, transition: Transition) -> Result<(), FlowError> {
+        match transition {
+            Transition::None => {}
+            Transition::Push(flow) => self.flows.push(flow),
+            Transition::Pop => {
+                self.flows.pop();
+                if let Some(flow) = self.flows.last_mut() {
+                    flow.resume();
+                }
+            }
+            Transition::PopWith(result) => {
+                self.flows.pop();
+                if let Some(flow) = self.flows.last_mut() {
+                    flow.resume();
+                    let transition = flow.handle_result(result)?;
+                    self.apply(transition)?;
+                }
+            }
+            Transition::Replace(flow) => {
+                self.flows.pop();
+                self.flows.push(flow);
+            }
+            Transition::Quit => self.flows.clear(),
+        }
+
+        Ok(())
     }
 }
 
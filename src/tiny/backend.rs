@@ -0,0 +1,226 @@
+use std::{cell::RefCell, collections::VecDeque, fs, path::Path, rc::Rc};
+
+use crate::{input::Event, math::Size, platform::KeyEvent, screen::Screen};
+
+/// Where the app gets its input from and presents its frames to.
+pub trait Backend {
+    /// Current size of the display.
+    fn size(&self) -> Size;
+
+    /// Returns the next pending event, or `None` when there are no more events this frame.
+    fn poll_event(&mut self) -> Option<Event>;
+
+    /// Displays a finished frame.
+    fn present(&mut self, screen: &Screen);
+
+    /// Whether the backend will never produce input again, like a finished script. The app stops when it is.
+    fn is_closed(&self) -> bool {
+        false
+    }
+}
+
+enum ScriptStep {
+    Event(Event),
+    Wait(usize),
+}
+
+/// Frames presented to a [`HeadlessBackend`], shared so they can be inspected after the app ran.
+#[derive(Clone, Default)]
+pub struct CapturedFrames(Rc<RefCell<Vec<Screen>>>);
+
+impl CapturedFrames {
+    pub fn len(&self) -> usize {
+        self.0.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.borrow().is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<Screen> {
+        self.0.borrow().get(index).cloned()
+    }
+
+    pub fn last(&self) -> Option<Screen> {
+        self.0.borrow().last().cloned()
+    }
+
+    fn push(&self, screen: Screen) {
+        self.0.borrow_mut().push(screen);
+    }
+}
+
+/// In-memory backend playing a scripted sequence of events and capturing the rendered frames,
+/// to run apps without a terminal.
+pub struct HeadlessBackend {
+    size: Size,
+    script: VecDeque<ScriptStep>,
+    frames_to_wait: usize,
+    frames: CapturedFrames,
+}
+
+impl HeadlessBackend {
+    pub fn new(size: Size) -> Self {
+        HeadlessBackend {
+            size,
+            script: VecDeque::new(),
+            frames_to_wait: 0,
+            frames: CapturedFrames::default(),
+        }
+    }
+
+    /// Adds a key press to the script, delivered in the same frame as the previous events.
+    pub fn press(mut self, key: impl Into<KeyEvent>) -> Self {
+        self.script
+            .push_back(ScriptStep::Event(Event::Key(key.into())));
+        self
+    }
+
+    /// Adds a terminal resize to the script.
+    pub fn resize(mut self, size: Size) -> Self {
+        self.script
+            .push_back(ScriptStep::Event(Event::Resize(size)));
+        self
+    }
+
+    /// Adds a focus change of the terminal window to the script.
+    pub fn focus(mut self, gained: bool) -> Self {
+        let event = if gained {
+            Event::FocusGained
+        } else {
+            Event::FocusLost
+        };
+        self.script.push_back(ScriptStep::Event(event));
+        self
+    }
+
+    /// Lets `frames` frames go by before delivering the rest of the script.
+    pub fn wait(mut self, frames: usize) -> Self {
+        self.script.push_back(ScriptStep::Wait(frames));
+        self
+    }
+
+    pub fn frames(&self) -> CapturedFrames {
+        self.frames.clone()
+    }
+}
+
+impl Backend for HeadlessBackend {
+    fn size(&self) -> Size {
+        self.size
+    }
+
+    fn poll_event(&mut self) -> Option<Event> {
+        if self.frames_to_wait > 0 {
+            return None;
+        }
+
+        match self.script.pop_front()? {
+            ScriptStep::Event(event) => {
+                if let Event::Resize(size) = event {
+                    self.size = size;
+                }
+                Some(event)
+            }
+            ScriptStep::Wait(frames) => {
+                self.frames_to_wait = frames;
+                None
+            }
+        }
+    }
+
+    fn present(&mut self, screen: &Screen) {
+        self.frames.push(screen.clone());
+        self.frames_to_wait = self.frames_to_wait.saturating_sub(1);
+    }
+
+    fn is_closed(&self) -> bool {
+        self.script.is_empty() && self.frames_to_wait == 0
+    }
+}
+
+/// Key presses to replay, each at the frame it was pressed.
+#[derive(Debug, Clone, Default)]
+pub struct Replay {
+    keys: VecDeque<(usize, KeyEvent)>,
+}
+
+impl Replay {
+    /// Parses `<frame> <key>` lines such as `30 ctrl+z`, `#` starts a comment.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut keys = VecDeque::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let error = |message: String| format!("Line {}: {}", number + 1, message);
+            let (frame, key) = line
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| error(format!("Expected 'frame key', got '{line}'")))?;
+            let frame = frame
+                .parse()
+                .map_err(|_| error(format!("Invalid frame '{frame}'")))?;
+            keys.push_back((frame, key.parse().map_err(error)?));
+        }
+
+        keys.make_contiguous().sort_by_key(|(frame, _)| *frame);
+        Ok(Replay { keys })
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        fs::read_to_string(path)
+            .map_err(|error| error.to_string())
+            .and_then(|text| Replay::parse(&text))
+            .map_err(|error| format!("{}: {}", path.display(), error))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+/// Backend pressing the keys of a replay on top of the input of another backend.
+pub struct ReplayBackend {
+    backend: Box<dyn Backend>,
+    replay: Replay,
+    frame: usize,
+}
+
+impl ReplayBackend {
+    pub fn new(backend: Box<dyn Backend>, replay: Replay) -> Self {
+        ReplayBackend {
+            backend,
+            replay,
+            frame: 0,
+        }
+    }
+}
+
+impl Backend for ReplayBackend {
+    fn size(&self) -> Size {
+        self.backend.size()
+    }
+
+    fn poll_event(&mut self) -> Option<Event> {
+        if let Some(event) = self.backend.poll_event() {
+            return Some(event);
+        }
+
+        let (frame, _) = self.replay.keys.front()?;
+        if *frame > self.frame {
+            return None;
+        }
+        self.replay.keys.pop_front().map(|(_, key)| Event::Key(key))
+    }
+
+    fn present(&mut self, screen: &Screen) {
+        self.backend.present(screen);
+        self.frame += 1;
+    }
+
+    fn is_closed(&self) -> bool {
+        self.backend.is_closed() && self.replay.is_empty()
+    }
+}
@@ -0,0 +1,268 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use crate::{canvas::Canvas, screen::str_width, style::Style};
+
+/// The font bundled with tiny, 5 lines of full blocks.
+const BLOCK: &str = include_str!("fonts/block.flf");
+
+/// Characters every FIGlet font defines after the printable ASCII ones, in this order.
+const DEUTSCH: [char; 7] = ['Ä', 'Ö', 'Ü', 'ä', 'ö', 'ü', 'ß'];
+
+/// Font drawing text as large banners, read from the FIGlet `.flf` format.
+///
+/// Letters are drawn side by side at their full width, the kerning and smushing rules
+/// of the font are not applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Font {
+    height: usize,
+    glyphs: HashMap<char, Vec<String>>,
+}
+
+impl Default for Font {
+    fn default() -> Self {
+        Font::block()
+    }
+}
+
+impl Font {
+    /// The font bundled with tiny, with the printable ASCII characters.
+    pub fn block() -> Self {
+        Font::parse(BLOCK).expect("the bundled font is valid")
+    }
+
+    /// Reads a `.flf` FIGlet font file.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text =
+            fs::read_to_string(path).map_err(|error| format!("{}: {error}", path.display()))?;
+        Font::parse(&text).map_err(|error| format!("{}: {error}", path.display()))
+    }
+
+    /// Parses a font in the FIGlet format: a `flf2a` header line, comment lines,
+    /// then the lines of each character ending with an end mark such as `@`.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut lines = text.lines();
+        let header = lines.next().ok_or("Empty font")?;
+        let signature = header.strip_prefix("flf2a").ok_or("Not a FIGlet font")?;
+        let mut chars = signature.chars();
+        let hardblank = chars.next().ok_or("Invalid font header")?;
+        let parameters: Vec<i64> = chars
+            .as_str()
+            .split_whitespace()
+            .map(|parameter| parameter.parse().map_err(|_| "Invalid font header"))
+            .collect::<Result<_, _>>()?;
+        let [height, _baseline, _max_length, _old_layout, comment_lines, ..] = parameters[..]
+        else {
+            return Err("Invalid font header".to_string());
+        };
+        if height < 1 {
+            return Err("Invalid font height".to_string());
+        }
+
+        let mut lines = lines.skip(comment_lines.max(0) as usize).peekable();
+        let mut font = Font {
+            height: height as usize,
+            glyphs: HashMap::new(),
+        };
+        let mut read_glyph = |c: char, lines: &mut dyn Iterator<Item = &str>| {
+            let glyph = (0..font.height)
+                .map(|_| lines.next().map(|line| glyph_line(line, hardblank)))
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| format!("Missing lines for character '{c}'"))?;
+            font.glyphs.insert(c, glyph);
+            Ok::<(), String>(())
+        };
+
+        for c in (32..=126u8).map(char::from) {
+            read_glyph(c, &mut lines)?;
+        }
+        // The German characters and the ones tagged with their code are optional.
+        for c in DEUTSCH {
+            if lines.peek().is_none() {
+                break;
+            }
+            read_glyph(c, &mut lines)?;
+        }
+        while let Some(tag) = lines.next() {
+            if tag.trim().is_empty() {
+                continue;
+            }
+            let code = tag.split_whitespace().next().unwrap_or_default();
+            let code =
+                parse_code(code).ok_or_else(|| format!("Invalid character code '{code}'"))?;
+            // Negative codes are not Unicode characters, their lines are skipped.
+            let c = u32::try_from(code)
+                .ok()
+                .and_then(char::from_u32)
+                .unwrap_or('\0');
+            read_glyph(c, &mut lines)?;
+        }
+        font.glyphs.remove(&'\0');
+
+        Ok(font)
+    }
+
+    /// Number of lines of the banners.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Lines of the banner of `text`, characters missing from the font are left out.
+    pub fn render(&self, text: &str) -> Vec<String> {
+        let mut lines = vec![String::new(); self.height];
+        for glyph in text.chars().filter_map(|c| self.glyphs.get(&c)) {
+            for (line, glyph_line) in lines.iter_mut().zip(glyph) {
+                line.push_str(glyph_line);
+            }
+        }
+        lines
+    }
+
+    /// Number of columns taken by the banner of `text`.
+    pub fn width(&self, text: &str) -> usize {
+        self.render(text)
+            .iter()
+            .map(|line| str_width(line))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Lines of the banner of `text`, or `text` itself when the banner is wider than `width`.
+    pub fn render_to_fit(&self, text: &str, width: usize) -> Vec<String> {
+        if self.width(text) <= width {
+            self.render(text)
+        } else {
+            vec![text.to_string()]
+        }
+    }
+
+    /// Draws the banner of `text` in the middle of the canvas, in plain text when it doesn't fit.
+    pub fn draw_centered(&self, canvas: &mut Canvas, text: &str, style: Style) {
+        let lines = self.render_to_fit(text, canvas.width());
+        let top = (canvas.height() as i32 - lines.len() as i32) / 2;
+        for (y, line) in lines.iter().enumerate() {
+            canvas.put_centered_str(top + y as i32, line, style);
+        }
+    }
+}
+
+/// A line of a character without its end marks, with the hardblanks turned into spaces.
+fn glyph_line(line: &str, hardblank: char) -> String {
+    let line = line.trim_end();
+    let line = match line.chars().last() {
+        Some(end_mark) => line.trim_end_matches(end_mark),
+        None => line,
+    };
+    line.replace(hardblank, " ")
+}
+
+/// Parses a character code, written in decimal, in hexadecimal after `0x` or in octal after `0`.
+fn parse_code(code: &str) -> Option<i64> {
+    let (negative, code) = match code.strip_prefix('-') {
+        Some(code) => (true, code),
+        None => (false, code),
+    };
+    let value = if let Some(hex) = code.strip_prefix("0x").or(code.strip_prefix("0X")) {
+        i64::from_str_radix(hex, 16).ok()?
+    } else if code.len() > 1 && code.starts_with('0') {
+        i64::from_str_radix(&code[1..], 8).ok()?
+    } else {
+        code.parse().ok()?
+    };
+    Some(if negative { -value } else { value })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A font of height 2 where every ASCII character is drawn `<c>$` over `__`,
+    /// followed by `extra` lines.
+    fn font_with(extra: &str) -> String {
+        let mut text = "flf2a$ 2 1 4 -1 1\nA test font.\n".to_string();
+        for c in (32..=126u8).map(char::from) {
+            let mark = if c == '@' { '#' } else { '@' };
+            text.push_str(&format!("{c}${mark}\n__{mark}{mark}\n"));
+        }
+        text + extra
+    }
+
+    #[test]
+    fn parses_the_bundled_font() {
+        let font = Font::block();
+        assert_eq!(font.height(), 5);
+        assert_eq!(font.render("Hi"), font.render("HI"));
+        assert_eq!(font.render("I")[0], "███ ");
+        assert_eq!(font.width("II"), 8);
+    }
+
+    #[test]
+    fn strips_end_marks_and_hardblanks() {
+        let font = Font::parse(&font_with("")).unwrap();
+        assert_eq!(font.render("a@"), ["a @ ", "____"]);
+        // Characters missing from the font are left out.
+        assert_eq!(font.render("é"), ["", ""]);
+    }
+
+    #[test]
+    fn rejects_bad_headers() {
+        assert_eq!(Font::parse(""), Err("Empty font".to_string()));
+        assert_eq!(Font::parse("flf2"), Err("Not a FIGlet font".to_string()));
+        assert_eq!(
+            Font::parse("flf2a$ 5 4 10"),
+            Err("Invalid font header".to_string())
+        );
+        assert_eq!(
+            Font::parse("flf2a$ 0 0 10 -1 0"),
+            Err("Invalid font height".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_truncated_glyphs() {
+        let mut text = font_with("");
+        text.truncate(text.trim_end().rfind('\n').unwrap());
+        assert_eq!(
+            Font::parse(&text),
+            Err("Missing lines for character '~'".to_string())
+        );
+    }
+
+    #[test]
+    fn reads_optional_characters() {
+        let deutsch = "Ä@\nÄ@@\n".repeat(7);
+        let tagged = "0x263A WHITE SMILING FACE\n:)@\n:(@@\n-2 not a character\nno@\nno@@\n";
+        let font = Font::parse(&font_with(&(deutsch + tagged))).unwrap();
+        assert_eq!(font.render("ß"), ["Ä", "Ä"]);
+        assert_eq!(font.render("☺"), [":)", ":("]);
+        assert_eq!(font.glyphs.len(), 95 + 7 + 1);
+
+        let truncated = font_with(&"Ä@\nÄ@@\n".repeat(7)) + "0x263A\n:)@\n";
+        assert_eq!(
+            Font::parse(&truncated),
+            Err("Missing lines for character '☺'".to_string())
+        );
+        let invalid = font_with(&"Ä@\nÄ@@\n".repeat(7)) + "0xZZ\n";
+        assert_eq!(
+            Font::parse(&invalid),
+            Err("Invalid character code '0xZZ'".to_string())
+        );
+    }
+
+    #[test]
+    fn parses_character_codes() {
+        assert_eq!(parse_code("65"), Some(65));
+        assert_eq!(parse_code("0x41"), Some(65));
+        assert_eq!(parse_code("0101"), Some(65));
+        assert_eq!(parse_code("0"), Some(0));
+        assert_eq!(parse_code("-2"), Some(-2));
+        assert_eq!(parse_code("09"), None);
+    }
+
+    #[test]
+    fn falls_back_to_plain_text() {
+        let font = Font::block();
+        let width = font.width("GO");
+        assert_eq!(font.render_to_fit("GO", width), font.render("GO"));
+        assert_eq!(font.render_to_fit("GO", width - 1), ["GO"]);
+    }
+}
@@ -0,0 +1,263 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    grid::Grid,
+    math::{Direction, Position, Rect, Size},
+    screen::{char_width, str_width, Screen, ScreenCell, WIDE_CONTINUATION},
+    style::Style,
+};
+
+/// Characters of the border drawn by [`Canvas::draw_box`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoxStyle {
+    /// `+`, `-` and `|`, for terminals without box-drawing characters.
+    Ascii,
+    Single,
+    Double,
+    Rounded,
+    /// The same character all around.
+    Char(char),
+}
+
+impl BoxStyle {
+    /// Top left, top right, bottom left and bottom right corners, then the horizontal and vertical sides.
+    fn glyphs(self) -> [char; 6] {
+        match self {
+            BoxStyle::Ascii => ['+', '+', '+', '+', '-', '|'],
+            BoxStyle::Single => ['┌', '┐', '└', '┘', '─', '│'],
+            BoxStyle::Double => ['╔', '╗', '╚', '╝', '═', '║'],
+            BoxStyle::Rounded => ['╭', '╮', '╰', '╯', '─', '│'],
+            BoxStyle::Char(c) => [c; 6],
+        }
+    }
+}
+
+type Layer = Grid<Option<ScreenCell>>;
+
+/// Transparent layers a flow draws on, composed onto the screen from the lowest to the highest.
+pub struct Layers {
+    size: Size,
+    layers: BTreeMap<i32, Layer>,
+}
+
+impl Layers {
+    pub fn new(size: Size) -> Self {
+        Layers {
+            size,
+            layers: BTreeMap::new(),
+        }
+    }
+
+    pub fn size(&self) -> Size {
+        self.size
+    }
+
+    /// Makes every cell transparent again, keeping the layers for the next flow.
+    pub fn clear(&mut self) {
+        for layer in self.layers.values_mut() {
+            layer.fill(None);
+        }
+    }
+
+    /// Copies the drawn cells onto the screen, higher layers on top.
+    pub fn compose_onto(&self, screen: &mut Screen) {
+        for layer in self.layers.values() {
+            // The screen covers the right half of wide glyphs itself.
+            for (position, cell) in layer.iter() {
+                match cell {
+                    Some(cell) if cell.c != WIDE_CONTINUATION => {
+                        screen.put_styled_char(position, cell.c, cell.style);
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fn layer_mut(&mut self, z: i32) -> &mut Layer {
+        let size = self.size;
+        self.layers
+            .entry(z)
+            .or_insert_with(|| Grid::new(size, None))
+    }
+}
+
+/// What flows render into. Positions are relative to the top left of the canvas
+/// and anything outside of it is clipped.
+///
+/// A canvas draws on layer 0, [`Canvas::layer`] gives one drawing above or below it
+/// whatever the order things are drawn in, and [`Canvas::region`] one limited to a part of it.
+pub struct Canvas<'a> {
+    layers: &'a mut Layers,
+    z: i32,
+    /// Area of the canvas on the screen.
+    area: Rect,
+    /// Part of the area which is visible, smaller than the area when it doesn't fit in its parent.
+    clip: Rect,
+}
+
+impl<'a> Canvas<'a> {
+    /// A canvas covering the whole of the layers.
+    pub fn new(layers: &'a mut Layers) -> Self {
+        let area = Rect::new(Position { x: 0, y: 0 }, layers.size());
+        Canvas {
+            layers,
+            z: 0,
+            area,
+            clip: area,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.area.size.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.area.size.height
+    }
+
+    pub fn size(&self) -> Size {
+        self.area.size
+    }
+
+    /// A canvas drawing on part of this one, with its own top left as origin.
+    pub fn region(&mut self, rect: Rect) -> Canvas<'_> {
+        let area = Rect::new(self.to_screen(rect.origin), rect.size);
+        Canvas {
+            layers: self.layers,
+            z: self.z,
+            area,
+            clip: self.clip.intersection(area),
+        }
+    }
+
+    /// The same canvas drawing on layer `z`, which covers the layers with a lower `z`.
+    pub fn layer(&mut self, z: i32) -> Canvas<'_> {
+        Canvas {
+            layers: self.layers,
+            z,
+            area: self.area,
+            clip: self.clip,
+        }
+    }
+
+    pub fn put_char(&mut self, position: Position, c: char) {
+        self.put_styled_char(position, c, Style::default());
+    }
+
+    /// Writes a character, a wide one also covers the cell on its right
+    /// and is replaced by a space when that cell is clipped.
+    pub fn put_styled_char(&mut self, position: Position, c: char, style: Style) {
+        let position = self.to_screen(position);
+        if !self.clip.contains(position) {
+            return;
+        }
+
+        let mut c = c;
+        if char_width(c) == 2 {
+            let right = position + Direction { x: 1, y: 0 };
+            if self.clip.contains(right) {
+                let continuation = ScreenCell {
+                    c: WIDE_CONTINUATION,
+                    style,
+                };
+                self.layers.layer_mut(self.z).set(right, Some(continuation));
+            } else {
+                c = ' ';
+            }
+        }
+        let cell = ScreenCell { c, style };
+        self.layers.layer_mut(self.z).set(position, Some(cell));
+    }
+
+    /// Writes a string on a single line starting at `position`.
+    pub fn put_str(&mut self, position: Position, string: &str) {
+        self.put_styled_str(position, string, Style::default());
+    }
+
+    pub fn put_styled_str(&mut self, position: Position, string: &str, style: Style) {
+        let mut x = position.x;
+        for c in string.chars() {
+            self.put_styled_char(Position { x, y: position.y }, c, style);
+            x += char_width(c) as i32;
+        }
+    }
+
+    /// Writes a string horizontally centred on line `y`.
+    pub fn put_centered_str(&mut self, y: i32, string: &str, style: Style) {
+        let x = (self.width() as i32 - str_width(string) as i32) / 2;
+        self.put_styled_str(Position { x, y }, string, style);
+    }
+
+    /// Changes the style of a cell drawn on this layer, keeping its character.
+    pub fn set_style(&mut self, position: Position, style: Style) {
+        let position = self.to_screen(position);
+        if !self.clip.contains(position) {
+            return;
+        }
+        if let Some(Some(cell)) = self.layers.layer_mut(self.z).get_mut(position) {
+            cell.style = style;
+        }
+    }
+
+    /// Changes the style of the cells of `rect` drawn on this layer.
+    pub fn set_rect_style(&mut self, rect: Rect, style: Style) {
+        for y in 0..rect.size.height as i32 {
+            for x in 0..rect.size.width as i32 {
+                self.set_style(rect.origin + Direction { x, y }, style);
+            }
+        }
+    }
+
+    pub fn fill_rect(&mut self, rect: Rect, c: char, style: Style) {
+        for y in 0..rect.size.height as i32 {
+            for x in 0..rect.size.width as i32 {
+                let position = Position {
+                    x: rect.origin.x + x,
+                    y: rect.origin.y + y,
+                };
+                self.put_styled_char(position, c, style);
+            }
+        }
+    }
+
+    /// Draws the border of `rect`, leaving its inside as it is.
+    pub fn draw_box(&mut self, rect: Rect, box_style: BoxStyle, style: Style) {
+        if rect.size.width < 2 || rect.size.height < 2 {
+            return;
+        }
+
+        let [top_left, top_right, bottom_left, bottom_right, horizontal, vertical] =
+            box_style.glyphs();
+        let Position { x: left, y: top } = rect.origin;
+        let right = left + rect.size.width as i32 - 1;
+        let bottom = top + rect.size.height as i32 - 1;
+
+        for x in left + 1..right {
+            self.put_styled_char(Position { x, y: top }, horizontal, style);
+            self.put_styled_char(Position { x, y: bottom }, horizontal, style);
+        }
+        for y in top + 1..bottom {
+            self.put_styled_char(Position { x: left, y }, vertical, style);
+            self.put_styled_char(Position { x: right, y }, vertical, style);
+        }
+        self.put_styled_char(Position { x: left, y: top }, top_left, style);
+        self.put_styled_char(Position { x: right, y: top }, top_right, style);
+        self.put_styled_char(Position { x: left, y: bottom }, bottom_left, style);
+        self.put_styled_char(
+            Position {
+                x: right,
+                y: bottom,
+            },
+            bottom_right,
+            style,
+        );
+    }
+
+    fn to_screen(&self, position: Position) -> Position {
+        Position {
+            x: self.area.origin.x + position.x,
+            y: self.area.origin.y + position.y,
+        }
+    }
+}
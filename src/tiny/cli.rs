@@ -0,0 +1,296 @@
+use std::{fmt::Write, path::PathBuf, process::ExitCode};
+
+use toml::Value;
+
+use crate::{app::TinyApp, config::Config, math::Size};
+
+/// Options a game can accept on top of `--help`, `--version` and `key=value` settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flag {
+    Level,
+    LevelFile,
+    Seed,
+    Fps,
+    Size,
+    NoColor,
+    NoIntro,
+    Replay,
+    Difficulty,
+    SaveSlot,
+}
+
+impl Flag {
+    fn name(self) -> &'static str {
+        match self {
+            Flag::Level => "--level",
+            Flag::LevelFile => "--level-file",
+            Flag::Seed => "--seed",
+            Flag::Fps => "--fps",
+            Flag::Size => "--size",
+            Flag::NoColor => "--no-color",
+            Flag::NoIntro => "--no-intro",
+            Flag::Replay => "--replay",
+            Flag::Difficulty => "--difficulty",
+            Flag::SaveSlot => "--save-slot",
+        }
+    }
+
+    /// Placeholder of the value in the help, `None` for switches.
+    fn value_name(self) -> Option<&'static str> {
+        match self {
+            Flag::Level | Flag::Seed | Flag::Fps | Flag::SaveSlot => Some("N"),
+            Flag::Difficulty => Some("LEVEL"),
+            Flag::LevelFile => Some("PATH"),
+            Flag::Size => Some("WxH"),
+            Flag::Replay => Some("FILE"),
+            Flag::NoColor | Flag::NoIntro => None,
+        }
+    }
+
+    fn help(self) -> &'static str {
+        match self {
+            Flag::Level => "Start at level N, skipping the intro",
+            Flag::LevelFile => "Play the levels of a file, separated by blank lines",
+            Flag::Seed => "Seed of the random generator, to play the same game again",
+            Flag::Fps => "Frames per second",
+            Flag::Size => "Size of the board, e.g. 40x20",
+            Flag::NoColor => "Draw without colours",
+            Flag::NoIntro => "Skip the intro",
+            Flag::Replay => "Press the keys of the `frame key` lines of a file",
+            Flag::Difficulty => "easy, normal or hard",
+            Flag::SaveSlot => "Save the progress to slot N and resume from it",
+        }
+    }
+}
+
+/// Parsed command line of a game.
+#[derive(Debug, Clone, Default)]
+pub struct Args {
+    pub level: Option<i64>,
+    pub level_file: Option<PathBuf>,
+    pub seed: Option<i64>,
+    pub fps: Option<i64>,
+    pub size: Option<Size>,
+    pub no_color: bool,
+    pub no_intro: bool,
+    pub replay: Option<PathBuf>,
+    pub difficulty: Option<String>,
+    pub save_slot: Option<i64>,
+    /// Config settings given as `key=value`.
+    pub settings: Vec<String>,
+}
+
+impl Args {
+    /// Applies the settings and the flags standing for settings to a config,
+    /// where their values are checked like the ones of the config file.
+    pub fn apply_to(&self, config: &mut Config) -> Result<(), String> {
+        for setting in &self.settings {
+            config.set(setting)?;
+        }
+
+        let integers = [
+            ("level", self.level),
+            ("seed", self.seed),
+            ("fps", self.fps),
+            ("save_slot", self.save_slot),
+            ("grid.width", self.size.map(|size| size.width as i64)),
+            ("grid.height", self.size.map(|size| size.height as i64)),
+        ];
+        for (key, value) in integers {
+            if let Some(value) = value {
+                config.set_value(key, Value::Integer(value))?;
+            }
+        }
+
+        if let Some(path) = &self.level_file {
+            let path = path.to_string_lossy().into_owned();
+            config.set_value("level_file", Value::String(path))?;
+        }
+        if let Some(difficulty) = &self.difficulty {
+            config.set_value("difficulty", Value::String(difficulty.clone()))?;
+        }
+        Ok(())
+    }
+}
+
+/// What the game should do after parsing its command line.
+pub enum Invocation {
+    Run(Args),
+    /// Print the help or version text and exit.
+    Print(String),
+}
+
+/// Command line parser of a game, accepting the declared flags.
+pub struct Cli {
+    name: String,
+    version: String,
+    about: String,
+    flags: Vec<Flag>,
+}
+
+impl Cli {
+    pub fn new(name: &str, version: &str) -> Self {
+        Cli {
+            name: name.to_string(),
+            version: version.to_string(),
+            about: String::new(),
+            flags: vec![],
+        }
+    }
+
+    pub fn with_about(mut self, about: &str) -> Self {
+        self.about = about.to_string();
+        self
+    }
+
+    pub fn with_flags(mut self, flags: &[Flag]) -> Self {
+        self.flags.extend_from_slice(flags);
+        self
+    }
+
+    pub fn help(&self) -> String {
+        let mut help = format!("{} {}\n", self.name, self.version);
+        if !self.about.is_empty() {
+            let _ = writeln!(help, "{}", self.about);
+        }
+        let _ = writeln!(help, "\nUsage: {} [OPTIONS] [KEY=VALUE]...\n", self.name);
+        let _ = writeln!(help, "Options:");
+
+        let options: Vec<(String, &str)> = self
+            .flags
+            .iter()
+            .map(|flag| match flag.value_name() {
+                Some(value_name) => (format!("{} {}", flag.name(), value_name), flag.help()),
+                None => (flag.name().to_string(), flag.help()),
+            })
+            .chain([
+                ("-h, --help".to_string(), "Print this help"),
+                ("-V, --version".to_string(), "Print the version"),
+            ])
+            .collect();
+        let width = options.iter().map(|(option, _)| option.len()).max();
+        for (option, description) in &options {
+            let _ = writeln!(help, "  {option:<0$}  {description}", width.unwrap_or(0));
+        }
+
+        let _ = writeln!(
+            help,
+            "\nSettings of the config file can also be given as KEY=VALUE, e.g. fps=30."
+        );
+        help
+    }
+
+    /// Parses the command line and runs the app set up by `load_app`, or prints the help
+    /// or the version. Errors are reported prefixed with the name of the game.
+    pub fn run<S: Into<String>>(
+        &self,
+        args: impl IntoIterator<Item = S>,
+        load_app: impl FnOnce(&Args) -> Result<TinyApp, String>,
+    ) -> ExitCode {
+        let loaded = self.parse(args).and_then(|invocation| match invocation {
+            Invocation::Run(args) => load_app(&args).map(Some),
+            Invocation::Print(text) => {
+                print!("{text}");
+                Ok(None)
+            }
+        });
+        let mut app = match loaded {
+            Ok(Some(app)) => app,
+            Ok(None) => return ExitCode::SUCCESS,
+            Err(error) => {
+                eprintln!("{}: {error}", self.name);
+                return ExitCode::FAILURE;
+            }
+        };
+
+        if let Err(error) = app.run() {
+            eprintln!("{}: {error}", self.name);
+            return ExitCode::FAILURE;
+        }
+
+        ExitCode::SUCCESS
+    }
+
+    pub fn parse<S: Into<String>>(
+        &self,
+        args: impl IntoIterator<Item = S>,
+    ) -> Result<Invocation, String> {
+        let mut parsed = Args::default();
+        let mut args = args.into_iter().map(Into::into);
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(Invocation::Print(self.help())),
+                "-V" | "--version" => {
+                    return Ok(Invocation::Print(format!(
+                        "{} {}\n",
+                        self.name, self.version
+                    )))
+                }
+                _ => {}
+            }
+
+            if !arg.starts_with('-') {
+                parsed.settings.push(arg);
+                continue;
+            }
+
+            // Values are given either as `--flag value` or `--flag=value`.
+            let (name, inline_value) = match arg.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (arg.as_str(), None),
+            };
+            let flag = self
+                .flags
+                .iter()
+                .copied()
+                .find(|flag| flag.name() == name)
+                .ok_or_else(|| format!("Unknown option '{name}', see --help"))?;
+
+            let value = match flag.value_name() {
+                Some(value_name) => inline_value
+                    .or_else(|| args.next())
+                    .ok_or_else(|| format!("{name} expects a value {value_name}"))?,
+                None if inline_value.is_some() => {
+                    return Err(format!("{name} doesn't take a value"));
+                }
+                None => String::new(),
+            };
+
+            let integer = || {
+                value
+                    .parse::<i64>()
+                    .map_err(|_| format!("{name} expects a number, got '{value}'"))
+            };
+            match flag {
+                Flag::Level => {
+                    parsed.level = Some(integer()?);
+                    parsed.no_intro = true;
+                }
+                Flag::LevelFile => parsed.level_file = Some(PathBuf::from(&value)),
+                Flag::Seed => parsed.seed = Some(integer()?),
+                Flag::Fps => parsed.fps = Some(integer()?),
+                Flag::Size => {
+                    parsed.size = Some(parse_size(&value).ok_or_else(|| {
+                        format!("{name} expects a size like 40x20, got '{value}'")
+                    })?)
+                }
+                Flag::NoColor => parsed.no_color = true,
+                Flag::NoIntro => parsed.no_intro = true,
+                Flag::Replay => parsed.replay = Some(PathBuf::from(&value)),
+                Flag::Difficulty => parsed.difficulty = Some(value),
+                Flag::SaveSlot => parsed.save_slot = Some(integer()?),
+            }
+        }
+
+        Ok(Invocation::Run(parsed))
+    }
+}
+
+fn parse_size(value: &str) -> Option<Size> {
+    let (width, height) = value.split_once(['x', 'X'])?;
+    Some(Size::new(
+        width.trim().parse().ok()?,
+        height.trim().parse().ok()?,
+    ))
+}
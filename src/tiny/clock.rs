@@ -0,0 +1,85 @@
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Source of time for the app loop.
+pub trait Clock {
+    /// Time elapsed since the clock was created.
+    fn now(&self) -> Duration;
+
+    /// Waits for `duration` to elapse.
+    fn sleep(&mut self, duration: Duration);
+}
+
+/// Wall clock time, sleeping the thread.
+pub struct RealClock {
+    start: Instant,
+}
+
+impl RealClock {
+    pub fn new() -> Self {
+        RealClock {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Default for RealClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for RealClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    fn sleep(&mut self, duration: Duration) {
+        thread::sleep(duration);
+    }
+}
+
+/// Clock that only moves when told to, sleeping advances the time and returns immediately unless paced.
+#[derive(Default)]
+pub struct VirtualClock {
+    now: Duration,
+    /// Whether sleeping also waits for the real time to elapse.
+    paced: bool,
+}
+
+impl VirtualClock {
+    pub fn new() -> Self {
+        VirtualClock {
+            now: Duration::ZERO,
+            paced: false,
+        }
+    }
+
+    /// A virtual clock which also sleeps the thread, so that a run measured in virtual time
+    /// is watched at its normal speed.
+    pub fn paced() -> Self {
+        VirtualClock {
+            paced: true,
+            ..VirtualClock::new()
+        }
+    }
+
+    pub fn advance(&mut self, duration: Duration) {
+        self.now += duration;
+    }
+}
+
+impl Clock for VirtualClock {
+    fn now(&self) -> Duration {
+        self.now
+    }
+
+    fn sleep(&mut self, duration: Duration) {
+        self.advance(duration);
+        if self.paced {
+            thread::sleep(duration);
+        }
+    }
+}
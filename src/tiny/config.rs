@@ -0,0 +1,273 @@
+use std::{
+    env, fs, io,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+
+use toml::{Table, Value};
+
+use crate::{
+    banner::Font,
+    input::InputMap,
+    platform::KeyEvent,
+    style::{Color, Style},
+    tileset::{Tileset, Walls},
+};
+
+/// The settings read by `CommonSettings`, `keys` is a table.
+pub const COMMON_SETTINGS: [&str; 5] = ["fps", "tileset", "walls", "font", "keys.*"];
+
+/// Settings of a game, read from `$XDG_CONFIG_HOME/tiny/<game>.toml` and overridden
+/// by the command line, see `Args::apply_to`.
+///
+/// Values are checked by the typed getters, so that games can report mistakes before
+/// the terminal is set up.
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    table: Table,
+}
+
+impl Config {
+    pub fn new() -> Self {
+        Config::default()
+    }
+
+    /// Reads the user's config file of a game, an empty config if there is none.
+    pub fn load(game: &str) -> Result<Self, String> {
+        let Some(path) = Config::path(game) else {
+            return Ok(Config::new());
+        };
+
+        match fs::read_to_string(&path) {
+            Ok(text) => Config::parse(&text, &path),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Config::new()),
+            Err(error) => Err(format!("{}: {}", path.display(), error)),
+        }
+    }
+
+    pub fn parse(text: &str, path: &Path) -> Result<Self, String> {
+        let table = text
+            .parse()
+            .map_err(|error| format!("{}: {}", path.display(), error))?;
+        Ok(Config { table })
+    }
+
+    /// Path of the config file of a game.
+    pub fn path(game: &str) -> Option<PathBuf> {
+        config_dir().map(|dir| dir.join(format!("{game}.toml")))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+
+    /// Applies a `key=value` override, the value is read as TOML or else as a plain string.
+    pub fn set(&mut self, assignment: &str) -> Result<(), String> {
+        let (key, value) = assignment
+            .split_once('=')
+            .ok_or_else(|| format!("Expected 'key=value', got '{assignment}'"))?;
+        let value = value.trim();
+        let value = format!("value = {value}")
+            .parse::<Table>()
+            .ok()
+            .and_then(|mut table| table.remove("value"))
+            .unwrap_or_else(|| Value::String(value.to_string()));
+        self.set_value(key.trim(), value)
+    }
+
+    /// Sets a dotted key such as `grid.width`, creating the missing tables.
+    pub fn set_value(&mut self, key: &str, value: Value) -> Result<(), String> {
+        let mut path: Vec<&str> = key.split('.').collect();
+        let name = path.pop().filter(|name| !name.is_empty());
+        let Some(name) = name else {
+            return Err(format!("Invalid key '{key}'"));
+        };
+
+        let mut table = &mut self.table;
+        for section in path {
+            let entry = table
+                .entry(section)
+                .or_insert_with(|| Value::Table(Table::new()));
+            table = entry
+                .as_table_mut()
+                .ok_or_else(|| format!("'{section}' is not a table in '{key}'"))?;
+        }
+        table.insert(name.to_string(), value);
+        Ok(())
+    }
+
+    /// Reports the first setting which isn't one of the known dotted keys,
+    /// a known key ending with `.*` allows a whole table.
+    pub fn check_known(&self, known: &[&str]) -> Result<(), String> {
+        fn check(table: &Table, prefix: &str, known: &[&str]) -> Result<(), String> {
+            for (name, value) in table {
+                let key = format!("{prefix}{name}");
+                if known.contains(&format!("{key}.*").as_str()) {
+                    continue;
+                }
+
+                match value {
+                    Value::Table(table) => check(table, &format!("{key}."), known)?,
+                    _ if known.contains(&key.as_str()) => {}
+                    _ => return Err(format!("Unknown setting '{key}'")),
+                }
+            }
+            Ok(())
+        }
+
+        check(&self.table, "", known)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        let mut sections = key.split('.');
+        let first = self.table.get(sections.next()?)?;
+        sections.try_fold(first, |value, section| value.as_table()?.get(section))
+    }
+
+    pub fn get_integer(
+        &self,
+        key: &str,
+        range: RangeInclusive<i64>,
+    ) -> Result<Option<i64>, String> {
+        let Some(value) = self.get(key) else {
+            return Ok(None);
+        };
+
+        match value.as_integer() {
+            Some(integer) if range.contains(&integer) => Ok(Some(integer)),
+            _ => Err(format!(
+                "'{key}' must be an integer from {} to {}, got {value}",
+                range.start(),
+                range.end()
+            )),
+        }
+    }
+
+    pub fn get_str(&self, key: &str) -> Result<Option<&str>, String> {
+        match self.get(key) {
+            None => Ok(None),
+            Some(Value::String(string)) => Ok(Some(string)),
+            Some(value) => Err(format!("'{key}' must be a string, got {value}")),
+        }
+    }
+
+    /// A colour given by name, `#rrggbb` or xterm palette index.
+    pub fn get_color(&self, key: &str) -> Result<Option<Color>, String> {
+        let color = match self.get(key) {
+            None => return Ok(None),
+            Some(Value::String(name)) => name.parse(),
+            Some(Value::Integer(index)) => u8::try_from(*index)
+                .map(Color::Indexed)
+                .map_err(|_| format!("Colour index {index} is out of range")),
+            Some(value) => Err(format!("Expected a colour, got {value}")),
+        };
+        color.map(Some).map_err(|error| format!("'{key}': {error}"))
+    }
+
+    /// The given style with its foreground replaced by the colour of `key`, if set.
+    pub fn get_foreground(&self, key: &str, style: Style) -> Result<Style, String> {
+        Ok(match self.get_color(key)? {
+            Some(color) => style.fg(color),
+            None => style,
+        })
+    }
+
+    /// Rebinds the actions of the `[keys]` table, where each action takes
+    /// a list of keys or a comma separated string, e.g. `undo = ["u", "ctrl+z"]`.
+    pub fn apply_keys<Command: Clone>(
+        &self,
+        input_map: &mut InputMap<Command>,
+    ) -> Result<(), String> {
+        let Some(keys) = self.get("keys") else {
+            return Ok(());
+        };
+        let keys = keys
+            .as_table()
+            .ok_or_else(|| "'keys' must be a table of actions".to_string())?;
+
+        for (action, value) in keys {
+            let names: Vec<&str> = match value {
+                Value::String(names) => names.split(',').collect(),
+                Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+                _ => vec![],
+            };
+            if names.is_empty() {
+                return Err(format!(
+                    "'keys.{action}' must be a list of keys, got {value}"
+                ));
+            }
+
+            names
+                .into_iter()
+                .map(str::parse)
+                .collect::<Result<Vec<KeyEvent>, String>>()
+                .and_then(|keys| input_map.rebind(action, keys))
+                .map_err(|error| format!("'keys.{action}': {error}"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Settings shared by the games, each game reads its own ones next to them.
+#[derive(Clone)]
+pub struct CommonSettings<Command> {
+    /// Frames rendered per second.
+    pub fps: u32,
+    pub input_map: InputMap<Command>,
+    pub tileset: Tileset,
+    pub walls: Walls,
+    /// Font of the title and of the end screens.
+    pub font: Font,
+}
+
+impl<Command: Clone> CommonSettings<Command> {
+    pub fn new(fps: u32, input_map: InputMap<Command>) -> Self {
+        CommonSettings {
+            fps,
+            input_map,
+            tileset: Tileset::default(),
+            walls: Walls::default(),
+            font: Font::default(),
+        }
+    }
+
+    /// Reads the shared settings of `config`, after checking that the other ones
+    /// are among the game's `known` settings.
+    pub fn read(&mut self, config: &Config, known: &[&str]) -> Result<(), String> {
+        let known: Vec<&str> = COMMON_SETTINGS.iter().chain(known).copied().collect();
+        config.check_known(&known)?;
+        config.apply_keys(&mut self.input_map)?;
+
+        if let Some(fps) = config.get_integer("fps", 1..=240)? {
+            self.fps = fps as u32;
+        }
+        if let Some(tileset) = config.get_str("tileset")? {
+            self.tileset = tileset.parse()?;
+        }
+        if let Some(walls) = config.get_str("walls")? {
+            self.walls = walls.parse()?;
+        }
+        if let Some(path) = config.get_str("font")? {
+            self.font = Font::load(Path::new(path))?;
+        }
+        Ok(())
+    }
+}
+
+/// Directory of the user's tiny settings, `$XDG_CONFIG_HOME/tiny` or `~/.config/tiny`.
+pub fn config_dir() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .map(|dir| dir.join("tiny"))
+}
+
+/// Directory of the data saved by the games, `$XDG_DATA_HOME/tiny` or `~/.local/share/tiny`.
+pub fn data_dir() -> Option<PathBuf> {
+    env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+        .map(|dir| dir.join("tiny"))
+}
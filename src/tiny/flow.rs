@@ -1,119 +1,672 @@
-use std::iter;
+use std::{any::Any, error::Error, fmt, io, iter, str::FromStr};
 
-use crate::{app::Time, platform::Key};
+use crate::{
+    app::{Time, DEFAULT_TARGET_FPS},
+    banner::Font,
+    canvas::{BoxStyle, Canvas},
+    config::Config,
+    input::InputPolicy,
+    math::{Position, Rect, Size},
+    platform::{Key, KeyEvent},
+    screen::str_width,
+    style::{Color, Style},
+};
+
+/// What the app should do with the flow stack after a flow handled an event.
+pub enum Transition {
+    /// Keep the current flow.
+    None,
+    /// Pause the current flow and run a new one on top of it.
+    Push(Box<dyn Flow>),
+    /// Remove the current flow and resume the one below, the app quits when the stack is empty.
+    Pop,
+    /// Pop the current flow and hand a result to the one below through [`Flow::handle_result`].
+    PopWith(Box<dyn Any>),
+    /// Swap the current flow for a new one.
+    Replace(Box<dyn Flow>),
+    /// Quit the app.
+    Quit,
+}
+
+/// Error stopping the app, returned by a flow that can't continue.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlowError {
+    message: String,
+}
+
+impl FlowError {
+    pub fn new(message: impl Into<String>) -> Self {
+        FlowError {
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for FlowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for FlowError {}
+
+impl From<String> for FlowError {
+    fn from(message: String) -> Self {
+        FlowError::new(message)
+    }
+}
+
+impl From<&str> for FlowError {
+    fn from(message: &str) -> Self {
+        FlowError::new(message)
+    }
+}
+
+impl From<io::Error> for FlowError {
+    fn from(error: io::Error) -> Self {
+        FlowError::new(error.to_string())
+    }
+}
+
+pub type FlowResult = Result<Transition, FlowError>;
 
 pub trait Flow {
-    fn render(&self) {}
+    fn render(&self, _canvas: &mut Canvas) {}
 
-    fn handle_key(&mut self, _key: Key) -> Option<Box<dyn Flow>> {
-        None
+    /// Renders between the last two fixed updates, `alpha` going from 0 at the previous update to 1 at the last one.
+    fn render_interpolated(&self, canvas: &mut Canvas, _alpha: f32) {
+        self.render(canvas);
     }
 
-    fn update(&mut self, _time: &Time) -> Option<Box<dyn Flow>> {
-        None
+    fn handle_key(&mut self, _key: KeyEvent) -> FlowResult {
+        Ok(Transition::None)
+    }
+
+    fn update(&mut self, _time: &Time) -> FlowResult {
+        Ok(Transition::None)
+    }
+
+    /// Called when the flow this flow pushed pops and it is on top of the stack again,
+    /// before [`Flow::handle_result`].
+    fn resume(&mut self) {}
+
+    /// Receives the result of a flow this flow pushed, when it pops with [`Transition::PopWith`].
+    fn handle_result(&mut self, _result: Box<dyn Any>) -> FlowResult {
+        Ok(Transition::None)
     }
 
-    fn should_quit(&self) -> bool {
+    /// Called on every flow of the stack when the terminal is resized, only the transition of the top flow is applied.
+    fn handle_resize(&mut self, _size: Size) -> FlowResult {
+        Ok(Transition::None)
+    }
+
+    /// Overlays are drawn on top of the flow below them in the stack, instead of replacing it.
+    fn is_overlay(&self) -> bool {
         false
     }
+
+    fn input_policy(&self) -> InputPolicy {
+        InputPolicy::DeliverAll
+    }
+
+    /// Whether the app may pause while this flow is on top, flows waiting for input like menus don't need to.
+    fn can_pause(&self) -> bool {
+        true
+    }
+
+    /// Frame rate of the app while this flow is in the stack, unless a flow above it sets one.
+    fn target_fps(&self) -> Option<u32> {
+        None
+    }
+
+    /// Keys pausing the app while this flow is in the stack, unless a flow above it sets them.
+    fn pause_keys(&self) -> Option<Vec<KeyEvent>> {
+        None
+    }
 }
 
 pub struct DefaultFlow;
 impl Flow for DefaultFlow {}
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    /// The next harder difficulty, or easier with a negative step, clamped to the known ones.
+    pub fn step(self, step: i32) -> Self {
+        let index = Difficulty::ALL
+            .iter()
+            .position(|difficulty| *difficulty == self);
+        let index = (index.unwrap_or(1) as i32 + step).clamp(0, Difficulty::ALL.len() as i32 - 1);
+        Difficulty::ALL[index as usize]
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+        })
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Difficulty::ALL
+            .into_iter()
+            .find(|difficulty| difficulty.to_string() == s.trim().to_lowercase())
+            .ok_or_else(|| format!("Unknown difficulty '{s}', expected easy, normal or hard"))
+    }
+}
+
+/// Where and how a game starts, gathered by the intro or given by the launcher.
+#[derive(Debug, Clone, Default)]
+pub struct LaunchContext {
+    /// Index of the level to start at.
+    pub level: Option<usize>,
+    /// Seed of the random generator, to play the same game again.
+    pub seed: Option<u64>,
+    pub difficulty: Difficulty,
+    /// Slot where the game saves its progress and resumes from.
+    pub save_slot: Option<usize>,
+}
+
+/// A launch parameter the intro lets the player choose with left and right.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LaunchOption {
+    /// Choose the starting level among `count` levels.
+    Level {
+        count: usize,
+    },
+    Difficulty,
+}
+
+impl LaunchOption {
+    fn label(self, context: &LaunchContext) -> String {
+        match self {
+            LaunchOption::Level { count } => {
+                format!("< Level {} of {} >", context.level.unwrap_or(0) + 1, count)
+            }
+            LaunchOption::Difficulty => format!("< Difficulty: {} >", context.difficulty),
+        }
+    }
+
+    fn step(self, context: &mut LaunchContext, step: i32) {
+        match self {
+            LaunchOption::Level { count } => {
+                let level = context.level.unwrap_or(0) as i32 + step;
+                context.level = Some(level.clamp(0, count.max(1) as i32 - 1) as usize);
+            }
+            LaunchOption::Difficulty => context.difficulty = context.difficulty.step(step),
+        }
+    }
+}
+
+/// Starts a game, from its intro or from the launcher menu.
+///
+/// Games quit by popping their flow, which exits a standalone game and returns to the menu in the launcher.
 pub trait GameLauncher {
-    fn new() -> Self;
+    /// Sets up the launcher from the game's config, reporting invalid settings.
+    fn from_config(config: &Config) -> Result<Self, String>
+    where
+        Self: Sized;
+
+    /// Name listed by the launcher menu.
+    fn name(&self) -> &str;
+
+    /// The `(keys, action)` lines listed with the game.
+    fn controls(&self) -> Vec<(String, String)> {
+        vec![]
+    }
+
+    /// The context a game starts with unless the player chooses otherwise.
+    fn default_context(&self) -> LaunchContext {
+        LaunchContext::default()
+    }
+
+    /// The parameters of the context offered by the intro.
+    fn launch_options(&self) -> Vec<LaunchOption> {
+        vec![]
+    }
+
+    /// Frame rate the game runs at.
+    fn target_fps(&self) -> u32 {
+        DEFAULT_TARGET_FPS
+    }
+
+    /// Keys pausing the game, handled by the app rather than by the game's flows.
+    fn pause_keys(&self) -> Vec<KeyEvent> {
+        vec![]
+    }
 
-    fn launch_game(&self) -> Box<dyn Flow>;
+    /// Font of the title of the intro.
+    fn font(&self) -> Font {
+        Font::block()
+    }
+
+    fn launch_game(&self, context: &LaunchContext) -> Result<Box<dyn Flow>, FlowError>;
 }
 
-#[derive(Default)]
 pub struct IntroFlow<Launcher: GameLauncher> {
     app_name: String,
     controls: Vec<(String, String)>,
+    options: Vec<LaunchOption>,
+    selected_option: usize,
+    context: LaunchContext,
+    font: Font,
     launcher: Launcher,
 }
 
 impl<Launcher: GameLauncher> IntroFlow<Launcher> {
-    pub fn new(name: &str) -> Self {
+    pub fn new(name: &str, launcher: Launcher) -> Self {
         IntroFlow {
             app_name: name.to_string(),
-            launcher: Launcher::new(),
             controls: vec![],
+            options: launcher.launch_options(),
+            selected_option: 0,
+            context: launcher.default_context(),
+            font: launcher.font(),
+            launcher,
         }
     }
 
-    pub fn with_controls(mut self, controls: &[(&str, &str)]) -> Self {
+    /// Adds `(keys, action)` lines after the controls of the launcher.
+    pub fn with_controls<K: AsRef<str>, A: AsRef<str>>(mut self, controls: &[(K, A)]) -> Self {
         self.controls.extend(
             controls
                 .iter()
-                .map(|(key, action)| (key.to_string(), action.to_string())),
+                .map(|(key, action)| (key.as_ref().to_string(), action.as_ref().to_string())),
         );
         self
     }
 }
 
 impl<Launcher: GameLauncher> Flow for IntroFlow<Launcher> {
-    fn render(&self) {
-        let controls: Vec<String> = self
-            .controls
+    fn render(&self, canvas: &mut Canvas) {
+        let text_style = Style::new();
+        let empty = || (String::new(), text_style);
+
+        let title = format!("Tiny {}", self.app_name);
+        let title_style = Style::new().fg(Color::BrightYellow).bold();
+        let mut lines = vec![empty()];
+        lines.extend(
+            self.font
+                .render_to_fit(&title, canvas.width().saturating_sub(6))
+                .into_iter()
+                .map(|line| (line, title_style)),
+        );
+        lines.extend([empty(), empty()]);
+        lines.extend(
+            self.launcher
+                .controls()
+                .iter()
+                .chain(&self.controls)
+                .map(|(keys, action)| (format!("{} -> {}", keys, action), text_style)),
+        );
+        lines.push(empty());
+        if !self.options.is_empty() {
+            lines.extend(self.options.iter().enumerate().map(|(index, option)| {
+                let style = if index == self.selected_option {
+                    text_style.reverse()
+                } else {
+                    text_style
+                };
+                (option.label(&self.context), style)
+            }));
+            lines.push(empty());
+        }
+        lines.push(("Any key to start!".to_string(), text_style.bold()));
+        lines.push(empty());
+
+        let max_len = lines
             .iter()
-            .map(|(keys, action)| format!("{} -> {}", keys, action))
-            .collect();
+            .map(|(line, _)| str_width(line))
+            .max()
+            .unwrap_or(0);
+        let size = Size::new(max_len + 6, lines.len() + 2);
+        let rect = Rect::centered(canvas.size(), size);
 
-        let name = format!("Tiny {}", self.app_name);
-        let any_key_string = String::from("Any key to start!");
+        canvas.fill_rect(rect, ' ', text_style);
+        canvas.draw_box(
+            rect,
+            BoxStyle::Char('#'),
+            Style::new().fg(Color::BrightBlack),
+        );
+        let mut inside = canvas.region(rect.inset(1));
+        for (y, (line, style)) in lines.iter().enumerate() {
+            inside.put_centered_str(y as i32, line, *style);
+        }
+    }
 
-        let strings: Vec<&String> = iter::once(&name)
-            .chain(&controls)
-            .chain(iter::once(&any_key_string))
-            .collect();
+    fn handle_key(&mut self, key: KeyEvent) -> FlowResult {
+        // The arrows choose the launch options, any other key starts.
+        let option_count = self.options.len();
+        match key.key {
+            Key::Q => return Ok(Transition::Pop),
+            Key::Up | Key::Down if option_count > 0 => {
+                let step = if key.key == Key::Up {
+                    option_count - 1
+                } else {
+                    1
+                };
+                self.selected_option = (self.selected_option + step) % option_count;
+            }
+            Key::Left | Key::Right if option_count > 0 => {
+                let step = if key.key == Key::Left { -1 } else { 1 };
+                self.options[self.selected_option].step(&mut self.context, step);
+            }
+            _ => {
+                return Ok(Transition::Replace(
+                    self.launcher.launch_game(&self.context)?,
+                ))
+            }
+        }
+
+        Ok(Transition::None)
+    }
+
+    fn can_pause(&self) -> bool {
+        false
+    }
+}
+
+/// What happened to a menu after a key press.
+pub enum MenuEvent<Action> {
+    None,
+    Selected(Action),
+    Cancelled,
+}
+
+/// A titled list of options navigated with the keyboard.
+pub struct Menu<Action> {
+    title: String,
+    options: Vec<(String, Action)>,
+    selected: usize,
+}
+
+impl<Action: Clone> Menu<Action> {
+    pub fn new(title: &str) -> Self {
+        Menu {
+            title: title.to_string(),
+            options: vec![],
+            selected: 0,
+        }
+    }
+
+    pub fn with_option(mut self, label: &str, action: Action) -> Self {
+        self.options.push((label.to_string(), action));
+        self
+    }
+
+    pub fn selected(&self) -> Option<&Action> {
+        self.options.get(self.selected).map(|(_, action)| action)
+    }
+
+    /// Moves the selection with up/down (or w/s, k/j), selects with enter or space and cancels with escape.
+    pub fn handle_key(&mut self, key: KeyEvent) -> MenuEvent<Action> {
+        if self.options.is_empty() {
+            return match key.key {
+                Key::Escape => MenuEvent::Cancelled,
+                _ => MenuEvent::None,
+            };
+        }
+
+        match key.key {
+            Key::Up | Key::W | Key::K => {
+                self.selected = (self.selected + self.options.len() - 1) % self.options.len();
+            }
+            Key::Down | Key::S | Key::J | Key::Tab => {
+                self.selected = (self.selected + 1) % self.options.len();
+            }
+            Key::Home => self.selected = 0,
+            Key::End => self.selected = self.options.len() - 1,
+            Key::Enter | Key::Space => {
+                return MenuEvent::Selected(self.options[self.selected].1.clone());
+            }
+            Key::Escape => return MenuEvent::Cancelled,
+            _ => {}
+        }
 
-        let max_len = strings
+        MenuEvent::None
+    }
+
+    /// Number of lines taken by the menu box.
+    pub fn height(&self) -> usize {
+        self.options.len() + 4
+    }
+
+    /// Draws the menu in a box centred on the canvas.
+    pub fn render(&self, canvas: &mut Canvas) {
+        let labels: Vec<String> = self
+            .options
             .iter()
-            .max_by_key(|string| string.len())
-            .unwrap()
-            .len();
-
-        let intro_len = max_len + 6;
-        let text_len = intro_len - 2;
-
-        let print_centered = |string: String| {
-            let correction = usize::from(string.len() % 2 == 0);
-            let padding = (text_len - string.len()) / 2;
-            println!(
-                "#{}{}{}#",
-                " ".repeat(padding),
-                string,
-                " ".repeat(padding + correction)
+            .enumerate()
+            .map(|(index, (label, _))| {
+                let marker = if index == self.selected { '>' } else { ' ' };
+                format!("{marker} {label}")
+            })
+            .collect();
+
+        let inner_width = iter::once(self.title.len())
+            .chain(labels.iter().map(|label| label.len()))
+            .max()
+            .unwrap_or(0)
+            + 4;
+        let rect = Rect::centered(canvas.size(), Size::new(inner_width + 2, self.height()));
+
+        canvas.fill_rect(rect, ' ', Style::new());
+        canvas.draw_box(rect, BoxStyle::Ascii, Style::new().fg(Color::BrightBlack));
+
+        let mut inside = canvas.region(rect.inset(1));
+        inside.put_centered_str(0, &self.title, Style::new().fg(Color::BrightYellow).bold());
+        for (index, label) in labels.iter().enumerate() {
+            let style = if index == self.selected {
+                Style::new().reverse()
+            } else {
+                Style::new()
+            };
+            inside.put_styled_str(
+                Position {
+                    x: 1,
+                    y: 2 + index as i32,
+                },
+                label,
+                style,
             );
-        };
-        println!("{}", "#".repeat(intro_len));
-        println!("#{}#", " ".repeat(text_len));
-        print_centered(name);
-        println!("#{}#", " ".repeat(text_len));
-        println!("#{}#", " ".repeat(text_len));
-        for control in controls {
-            print_centered(control);
         }
-        println!("#{}#", " ".repeat(text_len));
-        print_centered(any_key_string);
-        println!("#{}#", " ".repeat(text_len));
-        println!("{}", "#".repeat(intro_len));
     }
+}
+
+/// Overlay letting the user pick one of a list of actions.
+/// The chosen action is handed to the flow below with [`Transition::PopWith`].
+pub struct MenuFlow<Action> {
+    menu: Menu<Action>,
+    cancel_action: Option<Action>,
+}
 
-    fn handle_key(&mut self, key: Key) -> Option<Box<dyn Flow>> {
-        if key == Key::Q {
-            return Some(Box::new(QuitFlow));
+impl<Action: Clone + 'static> MenuFlow<Action> {
+    pub fn new(title: &str) -> Self {
+        MenuFlow {
+            menu: Menu::new(title),
+            cancel_action: None,
         }
+    }
+
+    pub fn with_option(mut self, label: &str, action: Action) -> Self {
+        self.menu = self.menu.with_option(label, action);
+        self
+    }
 
-        Some(self.launcher.launch_game())
+    /// Action returned when the menu is cancelled with escape, by default it just pops.
+    pub fn with_cancel_action(mut self, action: Action) -> Self {
+        self.cancel_action = Some(action);
+        self
     }
 }
 
-pub struct QuitFlow;
-impl Flow for QuitFlow {
-    fn should_quit(&self) -> bool {
+impl<Action: Clone + 'static> Flow for MenuFlow<Action> {
+    fn render(&self, canvas: &mut Canvas) {
+        self.menu.render(canvas);
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> FlowResult {
+        Ok(match self.menu.handle_key(key) {
+            MenuEvent::None => Transition::None,
+            MenuEvent::Selected(action) => Transition::PopWith(Box::new(action)),
+            MenuEvent::Cancelled => match self.cancel_action.clone() {
+                Some(action) => Transition::PopWith(Box::new(action)),
+                None => Transition::Pop,
+            },
+        })
+    }
+
+    fn is_overlay(&self) -> bool {
         true
     }
+
+    fn can_pause(&self) -> bool {
+        false
+    }
+}
+
+/// The games listed by a [`LauncherFlow`].
+#[derive(Default)]
+pub struct GameRegistry {
+    games: Vec<Box<dyn GameLauncher>>,
+}
+
+impl GameRegistry {
+    pub fn new() -> Self {
+        GameRegistry::default()
+    }
+
+    pub fn with_game<Launcher: GameLauncher + 'static>(mut self, launcher: Launcher) -> Self {
+        self.games.push(Box::new(launcher));
+        self
+    }
+
+    pub fn games(&self) -> impl Iterator<Item = &dyn GameLauncher> {
+        self.games.iter().map(|game| game.as_ref())
+    }
+
+    pub fn get(&self, index: usize) -> Option<&dyn GameLauncher> {
+        self.games.get(index).map(|game| game.as_ref())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LauncherAction {
+    Launch(usize),
+    Quit,
+}
+
+/// Menu listing the registered games, a game runs on top of it until it pops.
+pub struct LauncherFlow {
+    registry: GameRegistry,
+    menu: Menu<LauncherAction>,
+    /// Index of the game running on top of the launcher, whose frame rate and pause keys the app uses.
+    running: Option<usize>,
+    /// Why the last game failed to start, shown until the next key.
+    error: Option<String>,
+}
+
+impl LauncherFlow {
+    pub fn new(title: &str, registry: GameRegistry) -> Self {
+        let menu = registry
+            .games()
+            .enumerate()
+            .fold(Menu::new(title), |menu, (index, game)| {
+                menu.with_option(game.name(), LauncherAction::Launch(index))
+            })
+            .with_option("Quit", LauncherAction::Quit);
+        LauncherFlow {
+            registry,
+            menu,
+            running: None,
+            error: None,
+        }
+    }
+
+    fn running_game(&self) -> Option<&dyn GameLauncher> {
+        self.registry.get(self.running?)
+    }
+}
+
+impl Flow for LauncherFlow {
+    fn render(&self, canvas: &mut Canvas) {
+        self.menu.render(canvas);
+
+        let menu_height = self.menu.height() as i32;
+        let top = (canvas.height() as i32 - menu_height).max(0) / 2 + menu_height + 1;
+        if let Some(error) = &self.error {
+            canvas.put_centered_str(top, error, Style::new().fg(Color::BrightRed).bold());
+            return;
+        }
+
+        // List the controls of the selected game under the menu.
+        let Some(LauncherAction::Launch(index)) = self.menu.selected() else {
+            return;
+        };
+        let Some(game) = self.registry.get(*index) else {
+            return;
+        };
+        for (y, (keys, action)) in game.controls().iter().enumerate() {
+            let line = format!("{keys} -> {action}");
+            canvas.put_centered_str(top + y as i32, &line, Style::new().fg(Color::BrightBlack));
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> FlowResult {
+        if key.key == Key::Q {
+            return Ok(Transition::Quit);
+        }
+
+        self.error = None;
+        Ok(match self.menu.handle_key(key) {
+            MenuEvent::Selected(LauncherAction::Launch(index)) => match self.registry.get(index) {
+                // A game which can't start doesn't take the other games down with it.
+                Some(game) => match game.launch_game(&game.default_context()) {
+                    Ok(flow) => {
+                        self.running = Some(index);
+                        Transition::Push(flow)
+                    }
+                    Err(error) => {
+                        self.error = Some(format!("{}: {error}", game.name()));
+                        Transition::None
+                    }
+                },
+                None => Transition::None,
+            },
+            MenuEvent::Selected(LauncherAction::Quit) | MenuEvent::Cancelled => Transition::Quit,
+            MenuEvent::None => Transition::None,
+        })
+    }
+
+    fn resume(&mut self) {
+        // The game has returned to the launcher.
+        self.running = None;
+    }
+
+    fn can_pause(&self) -> bool {
+        false
+    }
+
+    fn target_fps(&self) -> Option<u32> {
+        Some(self.running_game()?.target_fps())
+    }
+
+    fn pause_keys(&self) -> Option<Vec<KeyEvent>> {
+        Some(self.running_game()?.pause_keys())
+    }
 }
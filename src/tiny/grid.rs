@@ -0,0 +1,253 @@
+use std::{fmt, mem};
+
+use crate::math::{Direction, Position, Size};
+
+/// The four orthogonal directions, in the order `neighbours` visits them.
+const NEIGHBOUR_DIRECTIONS: [Direction; 4] = [
+    Direction { x: 0, y: -1 },
+    Direction { x: 1, y: 0 },
+    Direction { x: 0, y: 1 },
+    Direction { x: -1, y: 0 },
+];
+
+/// Rectangular board of cells, addressed by `Position` with `(0, 0)` at the top left.
+///
+/// Accesses are checked, positions outside the grid give `None` rather than panicking.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T: Clone> Grid<T> {
+    /// A grid of the given size filled with `value`.
+    pub fn new(size: Size, value: T) -> Self {
+        Grid {
+            cells: vec![value; size.width * size.height],
+            width: size.width,
+            height: size.height,
+        }
+    }
+
+    pub fn fill(&mut self, value: T) {
+        self.cells.fill(value);
+    }
+
+    /// Parses one row per line, mapping each character to a cell.
+    /// Rows shorter than the longest one are padded with `fill`.
+    pub fn parse(
+        text: &str,
+        fill: T,
+        mut cell: impl FnMut(char) -> Option<T>,
+    ) -> Result<Self, String> {
+        let mut rows = vec![];
+        for (number, line) in text.lines().enumerate() {
+            let unknown = |c| format!("Line {}: unknown cell '{c}'", number + 1);
+            let row = line
+                .chars()
+                .map(|c| cell(c).ok_or_else(|| unknown(c)))
+                .collect::<Result<Vec<T>, String>>()?;
+            rows.push(row);
+        }
+
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+        if width == 0 {
+            return Err("Empty grid".to_string());
+        }
+
+        let height = rows.len();
+        let mut cells = Vec::with_capacity(width * height);
+        for mut row in rows {
+            row.resize(width, fill.clone());
+            cells.extend(row);
+        }
+
+        Ok(Grid {
+            cells,
+            width,
+            height,
+        })
+    }
+}
+
+impl<T> Grid<T> {
+    /// A grid where each cell is computed from its position.
+    pub fn from_fn(size: Size, mut cell: impl FnMut(Position) -> T) -> Self {
+        let cells = (0..size.width * size.height)
+            .map(|index| cell(position_of(index, size.width)))
+            .collect();
+        Grid {
+            cells,
+            width: size.width,
+            height: size.height,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn size(&self) -> Size {
+        Size::new(self.width, self.height)
+    }
+
+    pub fn contains(&self, position: Position) -> bool {
+        self.index(position).is_some()
+    }
+
+    pub fn get(&self, position: Position) -> Option<&T> {
+        self.index(position).map(|index| &self.cells[index])
+    }
+
+    pub fn get_mut(&mut self, position: Position) -> Option<&mut T> {
+        self.index(position).map(|index| &mut self.cells[index])
+    }
+
+    /// Replaces a cell, returning the previous value or `None` outside the grid.
+    pub fn set(&mut self, position: Position, value: T) -> Option<T> {
+        self.get_mut(position).map(|cell| mem::replace(cell, value))
+    }
+
+    /// Every cell with its position, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Position, &T)> + '_ {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(|(index, cell)| (position_of(index, self.width), cell))
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> + '_ {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    /// Cells of row `y` from left to right, empty outside the grid.
+    pub fn row(&self, y: i32) -> impl Iterator<Item = &T> + '_ {
+        self.rows()
+            .nth(usize::try_from(y).unwrap_or(usize::MAX))
+            .unwrap_or_default()
+            .iter()
+    }
+
+    /// Cells of column `x` from top to bottom, empty outside the grid.
+    pub fn column(&self, x: i32) -> impl Iterator<Item = &T> + '_ {
+        let x = usize::try_from(x).unwrap_or(usize::MAX);
+        let height = if x < self.width { self.height } else { 0 };
+        (0..height).map(move |y| &self.cells[x + y * self.width])
+    }
+
+    /// Cells orthogonally next to `position` which are inside the grid, starting above
+    /// and going clockwise.
+    pub fn neighbours(&self, position: Position) -> impl Iterator<Item = (Position, &T)> + '_ {
+        NEIGHBOUR_DIRECTIONS
+            .into_iter()
+            .filter_map(move |direction| {
+                let neighbour = position + direction;
+                self.get(neighbour).map(|cell| (neighbour, cell))
+            })
+    }
+
+    fn index(&self, position: Position) -> Option<usize> {
+        let x = usize::try_from(position.x).ok()?;
+        let y = usize::try_from(position.y).ok()?;
+        (x < self.width && y < self.height).then_some(x + y * self.width)
+    }
+}
+
+/// One line of characters per row.
+impl<T: Copy + Into<char>> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.rows() {
+            let line: String = row.iter().map(|cell| (*cell).into()).collect();
+            writeln!(f, "{line}")?;
+        }
+        Ok(())
+    }
+}
+
+fn position_of(index: usize, width: usize) -> Position {
+    Position {
+        x: (index % width) as i32,
+        y: (index / width) as i32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(x: i32, y: i32) -> Position {
+        Position { x, y }
+    }
+
+    fn walls(text: &str) -> Result<Grid<char>, String> {
+        Grid::parse(text, ' ', |c| "# .".contains(c).then_some(c))
+    }
+
+    #[test]
+    fn parse_pads_short_rows() {
+        let grid = walls("###\n#\n# #").unwrap();
+        assert_eq!(grid.size(), Size::new(3, 3));
+        assert_eq!(grid.to_string(), "###\n#  \n# #\n");
+    }
+
+    #[test]
+    fn parse_reports_unknown_cells() {
+        assert_eq!(
+            walls("###\n#x#"),
+            Err("Line 2: unknown cell 'x'".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_rejects_empty_input() {
+        assert_eq!(walls(""), Err("Empty grid".to_string()));
+        assert_eq!(walls("\n\n"), Err("Empty grid".to_string()));
+    }
+
+    #[test]
+    fn accesses_outside_are_none() {
+        let mut grid = Grid::new(Size::new(2, 2), 0);
+        assert_eq!(grid.set(at(1, 1), 5), Some(0));
+        assert_eq!(grid.get(at(1, 1)), Some(&5));
+
+        for position in [at(-1, 0), at(0, -1), at(2, 0), at(0, 2)] {
+            assert!(!grid.contains(position));
+            assert_eq!(grid.get(position), None);
+            assert_eq!(grid.set(position, 1), None);
+        }
+        assert_eq!(grid, Grid::from_fn(Size::new(2, 2), |p| p.x * p.y * 5));
+    }
+
+    #[test]
+    fn rows_and_columns_outside_are_empty() {
+        let grid = Grid::from_fn(Size::new(3, 2), |p| p.x + p.y * 3);
+        assert_eq!(grid.row(1).copied().collect::<Vec<_>>(), [3, 4, 5]);
+        assert_eq!(grid.column(2).copied().collect::<Vec<_>>(), [2, 5]);
+
+        assert_eq!(grid.row(-1).count(), 0);
+        assert_eq!(grid.row(2).count(), 0);
+        assert_eq!(grid.column(-1).count(), 0);
+        assert_eq!(grid.column(3).count(), 0);
+    }
+
+    #[test]
+    fn neighbours_stay_inside() {
+        let grid = Grid::from_fn(Size::new(3, 3), |p| p.x + p.y * 3);
+        let neighbours = |position| {
+            grid.neighbours(position)
+                .map(|(_, cell)| *cell)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(neighbours(at(0, 0)), [1, 3]);
+        assert_eq!(neighbours(at(2, 0)), [5, 1]);
+        assert_eq!(neighbours(at(0, 2)), [3, 7]);
+        assert_eq!(neighbours(at(2, 2)), [5, 7]);
+        assert_eq!(neighbours(at(1, 1)), [1, 5, 7, 3]);
+    }
+}
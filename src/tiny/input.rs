@@ -0,0 +1,221 @@
+use std::collections::VecDeque;
+
+use crate::{
+    math::Size,
+    platform::{Key, KeyEvent, Modifiers},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    Key(KeyEvent),
+    /// The terminal was resized to the given size.
+    Resize(Size),
+    /// The terminal window got the focus back, for terminals reporting focus changes.
+    FocusGained,
+    /// The terminal window lost the focus.
+    FocusLost,
+}
+
+/// How the key events received during a frame are delivered to a flow, other events are always delivered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InputPolicy {
+    /// Every event is delivered in order.
+    #[default]
+    DeliverAll,
+    /// Only the most recent event is delivered.
+    KeepLast,
+    /// Consecutive identical events, such as a held down direction, are delivered once.
+    CoalesceRepeats,
+}
+
+/// Ordered queue of the input events received since the last frame.
+#[derive(Default)]
+pub struct EventQueue {
+    events: VecDeque<Event>,
+}
+
+impl EventQueue {
+    pub fn new() -> Self {
+        EventQueue {
+            events: VecDeque::new(),
+        }
+    }
+
+    pub fn push(&mut self, event: Event) {
+        self.events.push_back(event);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Removes all the queued events, filtered according to the policy.
+    pub fn drain(&mut self, policy: InputPolicy) -> Vec<Event> {
+        let mut events: Vec<Event> = self.events.drain(..).collect();
+        match policy {
+            InputPolicy::DeliverAll => {}
+            InputPolicy::KeepLast => {
+                let last_key = events
+                    .iter()
+                    .rposition(|event| matches!(event, Event::Key(_)));
+                let mut index = 0;
+                events.retain(|event| {
+                    let keep = !matches!(event, Event::Key(_)) || Some(index) == last_key;
+                    index += 1;
+                    keep
+                });
+            }
+            InputPolicy::CoalesceRepeats => events.dedup(),
+        }
+        events
+    }
+}
+
+/// A game action and the keys bound to it.
+#[derive(Debug, Clone)]
+pub struct Binding<Command> {
+    /// Name used to rebind the action in the user's key file.
+    pub action: String,
+    /// Short text shown in the controls list.
+    pub description: String,
+    pub keys: Vec<KeyEvent>,
+    pub command: Command,
+}
+
+/// Maps key events to game commands.
+///
+/// Games declare their default bindings, which the user may override from the `[keys]`
+/// table of the game's config, see `Config::apply_keys`.
+#[derive(Debug, Clone)]
+pub struct InputMap<Command> {
+    bindings: Vec<Binding<Command>>,
+}
+
+impl<Command: Clone> Default for InputMap<Command> {
+    fn default() -> Self {
+        InputMap::new()
+    }
+}
+
+impl<Command: Clone> InputMap<Command> {
+    pub fn new() -> Self {
+        InputMap { bindings: vec![] }
+    }
+
+    pub fn bind<K: Into<KeyEvent>>(
+        mut self,
+        action: &str,
+        description: &str,
+        keys: impl IntoIterator<Item = K>,
+        command: Command,
+    ) -> Self {
+        self.bindings.push(Binding {
+            action: action.to_string(),
+            description: description.to_string(),
+            keys: keys.into_iter().map(Into::into).collect(),
+            command,
+        });
+        self
+    }
+
+    pub fn bindings(&self) -> &[Binding<Command>] {
+        &self.bindings
+    }
+
+    /// Keys bound to an action.
+    pub fn keys(&self, action: &str) -> Vec<KeyEvent> {
+        self.bindings
+            .iter()
+            .filter(|binding| binding.action == action)
+            .flat_map(|binding| binding.keys.iter().copied())
+            .collect()
+    }
+
+    /// The command bound to a key event.
+    ///
+    /// A shifted key without a binding of its own falls back to the unshifted one,
+    /// so that letter bindings keep working with caps lock on.
+    pub fn get(&self, event: KeyEvent) -> Option<Command> {
+        self.find(event).or_else(|| {
+            (event.modifiers == Modifiers::SHIFT)
+                .then(|| self.find(KeyEvent::from(event.key)))
+                .flatten()
+        })
+    }
+
+    fn find(&self, event: KeyEvent) -> Option<Command> {
+        self.bindings
+            .iter()
+            .find(|binding| binding.keys.contains(&event))
+            .map(|binding| binding.command.clone())
+    }
+
+    /// Replaces the keys of an action, taking them away from any other action.
+    pub fn rebind(&mut self, action: &str, keys: Vec<KeyEvent>) -> Result<(), String> {
+        if !self.bindings.iter().any(|binding| binding.action == action) {
+            return Err(format!("Unknown action '{action}'"));
+        }
+
+        for binding in self.bindings.iter_mut() {
+            if binding.action == action {
+                binding.keys = keys.clone();
+            } else {
+                binding.keys.retain(|key| !keys.contains(key));
+            }
+        }
+        Ok(())
+    }
+
+    /// The controls text of the bindings, as `(keys, description)` pairs.
+    ///
+    /// Bindings sharing a description are listed together, e.g. `wasd/arrows` for four moves.
+    pub fn controls(&self) -> Vec<(String, String)> {
+        let mut descriptions: Vec<&str> = vec![];
+        for binding in &self.bindings {
+            if !binding.keys.is_empty() && !descriptions.contains(&binding.description.as_str()) {
+                descriptions.push(&binding.description);
+            }
+        }
+
+        descriptions
+            .into_iter()
+            .map(|description| {
+                let group: Vec<&Binding<Command>> = self
+                    .bindings
+                    .iter()
+                    .filter(|binding| binding.description == description)
+                    .collect();
+                let slots = group.iter().map(|binding| binding.keys.len()).max();
+                let keys: Vec<String> = (0..slots.unwrap_or_default())
+                    .map(|slot| {
+                        let keys: Vec<KeyEvent> = group
+                            .iter()
+                            .filter_map(|binding| binding.keys.get(slot).copied())
+                            .collect();
+                        describe_keys(&keys)
+                    })
+                    .collect();
+                (keys.join("/"), description.to_string())
+            })
+            .collect()
+    }
+}
+
+/// Shortens a set of keys for the controls text.
+fn describe_keys(keys: &[KeyEvent]) -> String {
+    let arrows = [Key::Up, Key::Down, Key::Left, Key::Right].map(KeyEvent::from);
+    if keys.len() == arrows.len() && arrows.iter().all(|arrow| keys.contains(arrow)) {
+        return "arrows".to_string();
+    }
+
+    let names: Vec<String> = keys.iter().map(KeyEvent::to_string).collect();
+    if names.len() > 1 && names.iter().all(|name| name.chars().count() == 1) {
+        names.concat()
+    } else {
+        names.join("/")
+    }
+}
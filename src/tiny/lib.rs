@@ -1,12 +1,32 @@
 pub mod app;
+pub mod backend;
+pub mod banner;
+pub mod canvas;
+pub mod cli;
+pub mod clock;
+pub mod config;
 pub mod flow;
+pub mod grid;
+pub mod input;
 pub mod math;
 pub mod platform;
+pub mod screen;
+pub mod style;
+pub mod tileset;
+pub mod viewport;
+
+#[path = "../snake/mod.rs"]
+pub mod snake;
+#[path = "../sokoban/mod.rs"]
+pub mod sokoban;
 
 pub mod prelude {
     pub use crate::app::{Time, TinyApp};
+    pub use crate::canvas::Canvas;
     pub use crate::flow::Flow;
-    pub use crate::math::{Direction, Position};
-    pub use crate::platform::Key;
+    pub use crate::math::{Direction, Position, Size};
+    pub use crate::platform::{Key, KeyEvent, Modifiers};
+    pub use crate::screen::Screen;
+    pub use crate::style::{Color, Style};
     pub use std::time::Duration;
 }
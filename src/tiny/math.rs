@@ -6,6 +6,91 @@ pub type Direction = DirectionT<i32>;
 pub type Positionf32 = PositionT<f32>;
 pub type Directionf32 = DirectionT<f32>;
 
+/// Dimensions of a rectangular area, in cells.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Size {
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Size {
+    pub fn new(width: usize, height: usize) -> Self {
+        Size { width, height }
+    }
+
+    /// Whether an area of size `other` fits inside this one.
+    pub fn contains(&self, other: Size) -> bool {
+        other.width <= self.width && other.height <= self.height
+    }
+}
+
+/// Rectangular area of cells, from its top left corner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub origin: Position,
+    pub size: Size,
+}
+
+impl Rect {
+    pub fn new(origin: Position, size: Size) -> Self {
+        Rect { origin, size }
+    }
+
+    /// An area of `size` centred in an area of size `outer`, kept at the top left if it doesn't fit.
+    pub fn centered(outer: Size, size: Size) -> Self {
+        let origin = Position {
+            x: (outer.width as i32 - size.width as i32).max(0) / 2,
+            y: (outer.height as i32 - size.height as i32).max(0) / 2,
+        };
+        Rect { origin, size }
+    }
+
+    pub fn contains(&self, position: Position) -> bool {
+        let (x, y) = (position.x - self.origin.x, position.y - self.origin.y);
+        (0..self.size.width as i32).contains(&x) && (0..self.size.height as i32).contains(&y)
+    }
+
+    /// The area covered by both rects, empty if they don't overlap.
+    pub fn intersection(&self, other: Rect) -> Rect {
+        let left = self.origin.x.max(other.origin.x);
+        let top = self.origin.y.max(other.origin.y);
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+        Rect {
+            origin: Position { x: left, y: top },
+            size: Size::new(
+                (right - left).max(0) as usize,
+                (bottom - top).max(0) as usize,
+            ),
+        }
+    }
+
+    /// The area inside a border of `margin` cells.
+    pub fn inset(&self, margin: usize) -> Rect {
+        Rect {
+            origin: self.origin
+                + Direction {
+                    x: margin as i32,
+                    y: margin as i32,
+                },
+            size: Size::new(
+                self.size.width.saturating_sub(2 * margin),
+                self.size.height.saturating_sub(2 * margin),
+            ),
+        }
+    }
+
+    /// First column to the right of the area.
+    fn right(&self) -> i32 {
+        self.origin.x + self.size.width as i32
+    }
+
+    /// First line below the area.
+    fn bottom(&self) -> i32 {
+        self.origin.y + self.size.height as i32
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct PositionT<T> {
     pub x: T,
@@ -1,23 +1,60 @@
 extern crate libc;
 extern crate termios;
 
+use std::collections::VecDeque;
+use std::fmt;
 use std::io;
+use std::panic;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Once, OnceLock};
 
-use std::io::Read;
+use std::io::{Read, Write};
+
+use crate::backend::Backend;
+use crate::input::Event;
+use crate::math::Size;
+use crate::screen::{Renderer, Screen};
+use crate::style::ColorSupport;
 
 use termios::VMIN;
 use termios::VTIME;
-use termios::{tcsetattr, Termios, ECHO, ICANON, TCSANOW};
+use termios::{tcsetattr, Termios, ECHO, ICANON, ISIG, TCSANOW};
 
 const STDIN: i32 = 0;
-const CLEAR: &str = "\x1B[2J\x1B[1;1H";
+const STDOUT: i32 = 1;
+const ESC: u8 = 0x1B;
+
+/// Switches to the alternate screen, hides the cursor and enables focus reporting.
+const ENTER_APP_SCREEN: &str = "\x1B[?1049h\x1B[?25l\x1B[?1004h";
+/// Disables focus reporting, shows the cursor and goes back to the normal screen.
+const LEAVE_APP_SCREEN: &str = "\x1B[?1004l\x1B[?25h\x1B[?1049l";
+
+const TERMINATION_SIGNALS: [libc::c_int; 4] =
+    [libc::SIGINT, libc::SIGTERM, libc::SIGHUP, libc::SIGQUIT];
+
+/// Terminal settings before the first platform was created, restored on exit.
+static ORIGINAL_TERMIOS: OnceLock<Termios> = OnceLock::new();
+/// Whether the terminal is currently set up for the app and needs restoring.
+static TERMINAL_ACTIVE: AtomicBool = AtomicBool::new(false);
+static INSTALL_HOOKS: Once = Once::new();
+/// Set by the SIGWINCH handler, the resize is reported by the next poll.
+static RESIZED: AtomicBool = AtomicBool::new(false);
+
+/// Size used when the terminal doesn't report one.
+const FALLBACK_SIZE: Size = Size {
+    width: 80,
+    height: 24,
+};
 
+/// Terminal backend, reading keys from stdin in raw mode and drawing to stdout.
 pub struct Platform {
     stdin: io::Stdin,
-    termios: Termios,
+    pending_input: VecDeque<u8>,
+    renderer: Renderer,
 }
 
-#[derive(PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Key {
     A,
     B,
@@ -45,38 +82,284 @@ pub enum Key {
     X,
     Y,
     Z,
+    Digit(u8),
+    /// Any other printable character, such as punctuation.
+    Char(char),
+    Up,
+    Down,
+    Left,
+    Right,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Insert,
+    Delete,
+    Enter,
+    Escape,
+    Backspace,
+    Tab,
+    Space,
+    /// Function keys F1 to F12.
+    Function(u8),
     Unknown,
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+}
+
+impl Modifiers {
+    pub const NONE: Modifiers = Modifiers {
+        shift: false,
+        ctrl: false,
+        alt: false,
+    };
+    pub const SHIFT: Modifiers = Modifiers {
+        shift: true,
+        ..Modifiers::NONE
+    };
+    pub const CTRL: Modifiers = Modifiers {
+        ctrl: true,
+        ..Modifiers::NONE
+    };
+    pub const ALT: Modifiers = Modifiers {
+        alt: true,
+        ..Modifiers::NONE
+    };
+
+    /// Decodes the xterm modifier parameter of a CSI sequence, e.g. the `5` in `ESC[1;5A`.
+    fn from_csi_param(param: u32) -> Self {
+        let bits = param.saturating_sub(1);
+        Modifiers {
+            shift: bits & 1 != 0,
+            alt: bits & 2 != 0,
+            ctrl: bits & 4 != 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyEvent {
+    pub key: Key,
+    pub modifiers: Modifiers,
+}
+
+impl KeyEvent {
+    pub fn new(key: Key, modifiers: Modifiers) -> Self {
+        KeyEvent { key, modifiers }
+    }
+
+    pub fn is_ctrl(&self, key: Key) -> bool {
+        self.key == key && self.modifiers.ctrl
+    }
+}
+
+impl From<Key> for KeyEvent {
+    fn from(key: Key) -> Self {
+        KeyEvent::new(key, Modifiers::NONE)
+    }
+}
+
+/// Names of the keys which aren't a single character, as written in bindings.
+const KEY_NAMES: [(Key, &str); 14] = [
+    (Key::Up, "up"),
+    (Key::Down, "down"),
+    (Key::Left, "left"),
+    (Key::Right, "right"),
+    (Key::Home, "home"),
+    (Key::End, "end"),
+    (Key::PageUp, "pageup"),
+    (Key::PageDown, "pagedown"),
+    (Key::Insert, "insert"),
+    (Key::Delete, "delete"),
+    (Key::Enter, "enter"),
+    (Key::Escape, "esc"),
+    (Key::Backspace, "backspace"),
+    (Key::Tab, "tab"),
+];
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some((_, name)) = KEY_NAMES.iter().find(|(key, _)| key == self) {
+            return f.write_str(name);
+        }
+
+        match self {
+            Key::Digit(digit) => write!(f, "{digit}"),
+            Key::Char(c) => write!(f, "{c}"),
+            Key::Space => f.write_str("space"),
+            Key::Function(number) => write!(f, "f{number}"),
+            Key::Unknown => f.write_str("unknown"),
+            // Letters are the only keys left.
+            letter => write!(f, "{}", format!("{letter:?}").to_lowercase()),
+        }
+    }
+}
+
+impl FromStr for Key {
+    type Err = String;
+
+    /// Parses a key name such as `w`, `up`, `esc` or `f1`, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_lowercase();
+        if let Some((key, _)) = KEY_NAMES.iter().find(|(_, key_name)| *key_name == name) {
+            return Ok(*key);
+        }
+
+        let mut chars = name.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => match Platform::translate_input(c) {
+                Key::Unknown => Err(format!("Unknown key '{s}'")),
+                key => Ok(key),
+            },
+            _ => match name.as_str() {
+                "escape" => Ok(Key::Escape),
+                "return" => Ok(Key::Enter),
+                "space" => Ok(Key::Space),
+                _ => name
+                    .strip_prefix('f')
+                    .and_then(|number| number.parse().ok())
+                    .filter(|number| (1..=12).contains(number))
+                    .map(Key::Function)
+                    .ok_or_else(|| format!("Unknown key '{s}'")),
+            },
+        }
+    }
+}
+
+impl fmt::Display for KeyEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.modifiers.ctrl {
+            f.write_str("ctrl+")?;
+        }
+        if self.modifiers.alt {
+            f.write_str("alt+")?;
+        }
+        if self.modifiers.shift {
+            f.write_str("shift+")?;
+        }
+        write!(f, "{}", self.key)
+    }
+}
+
+impl FromStr for KeyEvent {
+    type Err = String;
+
+    /// Parses a key with optional modifiers, such as `ctrl+z` or `shift+u`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        // The last `+` separates the key, unless the key is `+` itself.
+        let (prefix, key) = match s.strip_suffix('+') {
+            Some(rest) if rest.is_empty() || rest.ends_with('+') => (rest, "+"),
+            _ => s.rsplit_once('+').unwrap_or(("", s)),
+        };
+
+        let mut modifiers = Modifiers::NONE;
+        for modifier in prefix.split('+').filter(|modifier| !modifier.is_empty()) {
+            match modifier.trim().to_lowercase().as_str() {
+                "ctrl" | "control" => modifiers.ctrl = true,
+                "alt" | "meta" => modifiers.alt = true,
+                "shift" => modifiers.shift = true,
+                _ => return Err(format!("Unknown modifier '{modifier}' in '{s}'")),
+            }
+        }
+
+        Ok(KeyEvent::new(key.parse()?, modifiers))
+    }
+}
+
 impl Platform {
-    pub fn new() -> Self {
-        let termios = Termios::from_fd(STDIN).unwrap();
+    /// Sets the terminal up for the app, fails if stdin is not a terminal.
+    pub fn new() -> io::Result<Self> {
+        let termios = match ORIGINAL_TERMIOS.get() {
+            Some(termios) => *termios,
+            None => {
+                let termios = Termios::from_fd(STDIN)?;
+                *ORIGINAL_TERMIOS.get_or_init(|| termios)
+            }
+        };
         let mut new_termios = termios;
 
-        // no echo and canonical mode
-        new_termios.c_lflag &= !(ICANON | ECHO);
+        // no echo and canonical mode, no signals so that Ctrl+C and Ctrl+Z reach the app as key events.
+        new_termios.c_lflag &= !(ICANON | ECHO | ISIG);
 
         // set VMIN and VTIME to 0 not block on read when the buffer is empty.
         new_termios.c_cc[VMIN] = 0;
         new_termios.c_cc[VTIME] = 0;
 
-        tcsetattr(STDIN, TCSANOW, &new_termios).unwrap();
+        INSTALL_HOOKS.call_once(install_restore_hooks);
+
+        tcsetattr(STDIN, TCSANOW, &new_termios)?;
+        TERMINAL_ACTIVE.store(true, Ordering::SeqCst);
 
-        Platform {
+        let mut stdout = io::stdout().lock();
+        let _ = stdout.write_all(ENTER_APP_SCREEN.as_bytes());
+        let _ = stdout.flush();
+
+        Ok(Platform {
             stdin: io::stdin(),
-            termios,
+            pending_input: VecDeque::new(),
+            renderer: Renderer::new(),
+        })
+    }
+
+    /// Overrides the colour support detected from the environment.
+    pub fn with_color_support(mut self, color_support: ColorSupport) -> Self {
+        self.renderer.set_color_support(color_support);
+        self
+    }
+
+    /// Current size of the terminal.
+    pub fn size(&self) -> Size {
+        let mut winsize = libc::winsize {
+            ws_row: 0,
+            ws_col: 0,
+            ws_xpixel: 0,
+            ws_ypixel: 0,
+        };
+
+        let result = unsafe { libc::ioctl(STDOUT, libc::TIOCGWINSZ, &mut winsize) };
+        if result != 0 || winsize.ws_col == 0 || winsize.ws_row == 0 {
+            return FALLBACK_SIZE;
+        }
+
+        Size::new(winsize.ws_col as usize, winsize.ws_row as usize)
+    }
+
+    pub fn poll_event(&mut self) -> Option<Event> {
+        if RESIZED.swap(false, Ordering::SeqCst) {
+            return Some(Event::Resize(self.size()));
         }
+
+        self.poll_input()
     }
 
-    pub fn poll_input(&mut self) -> Option<char> {
-        let mut buffer = [0; 1];
-        self.stdin.read_exact(&mut buffer).ok()?;
+    fn poll_input(&mut self) -> Option<Event> {
+        // Escape sequences arrive as a burst of bytes, read everything available so they can be decoded whole.
+        let mut buffer = [0; 64];
+        if let Ok(count) = self.stdin.read(&mut buffer) {
+            self.pending_input.extend(&buffer[..count]);
+        }
+
+        if self.pending_input.is_empty() {
+            return None;
+        }
+
+        let (event, length) = decode_event(self.pending_input.make_contiguous());
+        self.pending_input.drain(..length);
 
-        buffer.first().map(|c| *c as char)
+        Some(event)
     }
 
-    pub fn clear_display() {
-        print!("{CLEAR}");
+    /// Writes a whole frame to the terminal at once.
+    fn write_frame(&mut self, frame: &str) {
+        let mut stdout = io::stdout().lock();
+        let _ = stdout.write_all(frame.as_bytes());
+        let _ = stdout.flush();
     }
 
     pub fn translate_input(c: char) -> Key {
@@ -107,19 +390,345 @@ impl Platform {
             'x' | 'X' => Key::X,
             'y' | 'Y' => Key::Y,
             'z' | 'Z' => Key::Z,
-            _ => todo!(),
+            '0'..='9' => Key::Digit(c as u8 - b'0'),
+            ' ' => Key::Space,
+            '\r' | '\n' => Key::Enter,
+            '\t' => Key::Tab,
+            '\x7F' | '\x08' => Key::Backspace,
+            '\x1B' => Key::Escape,
+            c if !c.is_control() => Key::Char(c),
+            _ => Key::Unknown,
+        }
+    }
+}
+
+/// Decodes the first event in `bytes`, returning it with the number of bytes it spans.
+fn decode_event(bytes: &[u8]) -> (Event, usize) {
+    match bytes {
+        // Focus reports, enabled with `ESC[?1004h`.
+        [ESC, b'[', b'I', ..] => (Event::FocusGained, 3),
+        [ESC, b'[', b'O', ..] => (Event::FocusLost, 3),
+        _ => {
+            let (key, length) = decode_key(bytes);
+            (Event::Key(key), length)
         }
     }
 }
 
-impl Default for Platform {
-    fn default() -> Self {
-        Self::new()
+/// Decodes the first key in `bytes`, returning it with the number of bytes it spans.
+fn decode_key(bytes: &[u8]) -> (KeyEvent, usize) {
+    match bytes {
+        [ESC, b'[', rest @ ..] => decode_csi(rest)
+            .map_or((Key::Unknown.into(), bytes.len()), |(event, length)| {
+                (event, length + 2)
+            }),
+        [ESC, b'O', code, ..] => (decode_ss3(*code).into(), 3),
+        // A lone escape is the Escape key, otherwise ESC prefixes the next key with Alt.
+        [ESC] | [ESC, ESC, ..] => (Key::Escape.into(), 1),
+        [ESC, rest @ ..] => {
+            let (mut event, length) = decode_key(rest);
+            event.modifiers.alt = true;
+            (event, length + 1)
+        }
+        [0x00, ..] => (KeyEvent::new(Key::Space, Modifiers::CTRL), 1),
+        // Ctrl+letter is sent as the letter's control byte, except the ones aliasing Backspace, Tab and Enter.
+        [control @ 0x01..=0x1A, ..] if !matches!(control, 0x08 | 0x09 | 0x0A | 0x0D) => {
+            let letter = (b'a' + control - 1) as char;
+            (
+                KeyEvent::new(Platform::translate_input(letter), Modifiers::CTRL),
+                1,
+            )
+        }
+        [first, ..] => {
+            let length = utf8_length(*first).min(bytes.len());
+            let event = std::str::from_utf8(&bytes[..length])
+                .ok()
+                .and_then(|string| string.chars().next())
+                .map_or(Key::Unknown.into(), |c| {
+                    let modifiers = if c.is_ascii_uppercase() {
+                        Modifiers::SHIFT
+                    } else {
+                        Modifiers::NONE
+                    };
+                    KeyEvent::new(Platform::translate_input(c), modifiers)
+                });
+            (event, length)
+        }
+        [] => (Key::Unknown.into(), 0),
+    }
+}
+
+/// Decodes a CSI sequence (`ESC [ params final`), `bytes` starts after the `[`.
+fn decode_csi(bytes: &[u8]) -> Option<(KeyEvent, usize)> {
+    let final_index = bytes.iter().position(|byte| (0x40..=0x7E).contains(byte))?;
+    let params: Vec<u32> = std::str::from_utf8(&bytes[..final_index])
+        .ok()?
+        .split(';')
+        .map(|param| param.parse().unwrap_or(0))
+        .collect();
+
+    let mut modifiers = params
+        .get(1)
+        .map_or(Modifiers::NONE, |param| Modifiers::from_csi_param(*param));
+
+    let key = match bytes[final_index] {
+        b'A' => Key::Up,
+        b'B' => Key::Down,
+        b'C' => Key::Right,
+        b'D' => Key::Left,
+        b'H' => Key::Home,
+        b'F' => Key::End,
+        b'P' => Key::Function(1),
+        b'Q' => Key::Function(2),
+        b'R' => Key::Function(3),
+        b'S' => Key::Function(4),
+        b'Z' => {
+            modifiers.shift = true;
+            Key::Tab
+        }
+        b'~' => match params.first() {
+            Some(1 | 7) => Key::Home,
+            Some(2) => Key::Insert,
+            Some(3) => Key::Delete,
+            Some(4 | 8) => Key::End,
+            Some(5) => Key::PageUp,
+            Some(6) => Key::PageDown,
+            Some(code @ 11..=15) => Key::Function((code - 10) as u8),
+            Some(code @ 17..=21) => Key::Function((code - 11) as u8),
+            Some(code @ 23..=24) => Key::Function((code - 12) as u8),
+            _ => Key::Unknown,
+        },
+        _ => Key::Unknown,
+    };
+
+    Some((KeyEvent::new(key, modifiers), final_index + 1))
+}
+
+/// Decodes the key code of an SS3 sequence (`ESC O code`), sent by some terminals for arrows and F1-F4.
+fn decode_ss3(code: u8) -> Key {
+    match code {
+        b'A' => Key::Up,
+        b'B' => Key::Down,
+        b'C' => Key::Right,
+        b'D' => Key::Left,
+        b'H' => Key::Home,
+        b'F' => Key::End,
+        b'P' => Key::Function(1),
+        b'Q' => Key::Function(2),
+        b'R' => Key::Function(3),
+        b'S' => Key::Function(4),
+        _ => Key::Unknown,
+    }
+}
+
+fn utf8_length(first_byte: u8) -> usize {
+    match first_byte {
+        0xF0..=0xF7 => 4,
+        0xE0..=0xEF => 3,
+        0xC0..=0xDF => 2,
+        _ => 1,
+    }
+}
+
+impl Backend for Platform {
+    fn size(&self) -> Size {
+        Platform::size(self)
+    }
+
+    fn poll_event(&mut self) -> Option<Event> {
+        Platform::poll_event(self)
+    }
+
+    fn present(&mut self, screen: &Screen) {
+        let frame = self.renderer.render(screen);
+        self.write_frame(&frame);
     }
 }
 
 impl Drop for Platform {
     fn drop(&mut self) {
-        tcsetattr(STDIN, TCSANOW, &self.termios).unwrap();
+        restore_terminal();
+    }
+}
+
+/// Puts the terminal back in the state it was before the platform was created.
+/// Only uses async-signal-safe calls so that it can run from a signal handler.
+fn restore_terminal() {
+    if !TERMINAL_ACTIVE.swap(false, Ordering::SeqCst) {
+        return;
+    }
+
+    unsafe {
+        libc::write(
+            STDOUT,
+            LEAVE_APP_SCREEN.as_ptr() as *const libc::c_void,
+            LEAVE_APP_SCREEN.len(),
+        );
+    }
+
+    if let Some(termios) = ORIGINAL_TERMIOS.get() {
+        let _ = tcsetattr(STDIN, TCSANOW, termios);
+    }
+}
+
+/// Makes sure the terminal is restored when the app panics or is terminated by a signal.
+fn install_restore_hooks() {
+    // Restore before the default hook runs so the panic message is printed on the normal screen.
+    let previous_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        restore_terminal();
+        previous_hook(info);
+    }));
+
+    for signal in TERMINATION_SIGNALS {
+        unsafe {
+            libc::signal(
+                signal,
+                handle_termination_signal as extern "C" fn(libc::c_int) as libc::sighandler_t,
+            );
+        }
+    }
+
+    unsafe {
+        libc::signal(
+            libc::SIGWINCH,
+            handle_resize_signal as extern "C" fn(libc::c_int) as libc::sighandler_t,
+        );
+    }
+}
+
+extern "C" fn handle_resize_signal(_signal: libc::c_int) {
+    RESIZED.store(true, Ordering::SeqCst);
+}
+
+extern "C" fn handle_termination_signal(signal: libc::c_int) {
+    restore_terminal();
+
+    // Terminate the way the signal would have without the handler.
+    unsafe {
+        libc::signal(signal, libc::SIG_DFL);
+        libc::raise(signal);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// All the keys in `bytes`, decoded one after the other.
+    fn decode_all(mut bytes: &[u8]) -> Vec<KeyEvent> {
+        let mut keys = Vec::new();
+        while !bytes.is_empty() {
+            let (key, length) = decode_key(bytes);
+            keys.push(key);
+            bytes = &bytes[length..];
+        }
+        keys
+    }
+
+    #[test]
+    fn decodes_csi_arrows_with_modifiers() {
+        assert_eq!(decode_key(b"\x1B[A"), (Key::Up.into(), 3));
+        assert_eq!(
+            decode_key(b"\x1B[1;5D"),
+            (KeyEvent::new(Key::Left, Modifiers::CTRL), 6)
+        );
+        assert_eq!(
+            decode_key(b"\x1B[1;2C"),
+            (KeyEvent::new(Key::Right, Modifiers::SHIFT), 6)
+        );
+        assert_eq!(
+            decode_key(b"\x1B[Z"),
+            (KeyEvent::new(Key::Tab, Modifiers::SHIFT), 3)
+        );
+    }
+
+    #[test]
+    fn decodes_tilde_keys() {
+        assert_eq!(decode_key(b"\x1B[3~"), (Key::Delete.into(), 4));
+        assert_eq!(decode_key(b"\x1B[5~"), (Key::PageUp.into(), 4));
+        assert_eq!(decode_key(b"\x1B[15~"), (Key::Function(5).into(), 5));
+        assert_eq!(decode_key(b"\x1B[24~"), (Key::Function(12).into(), 5));
+        assert_eq!(
+            decode_key(b"\x1B[3;3~"),
+            (KeyEvent::new(Key::Delete, Modifiers::ALT), 6)
+        );
+        assert_eq!(decode_key(b"\x1B[99~"), (Key::Unknown.into(), 5));
+    }
+
+    #[test]
+    fn decodes_ss3_keys() {
+        assert_eq!(decode_key(b"\x1BOP"), (Key::Function(1).into(), 3));
+        assert_eq!(decode_key(b"\x1BOA"), (Key::Up.into(), 3));
+        assert_eq!(decode_key(b"\x1BOz"), (Key::Unknown.into(), 3));
+    }
+
+    #[test]
+    fn decodes_alt_prefixes() {
+        assert_eq!(
+            decode_key(b"\x1Ba"),
+            (KeyEvent::new(Key::A, Modifiers::ALT), 2)
+        );
+        assert_eq!(decode_key(b"\x1B"), (Key::Escape.into(), 1));
+    }
+
+    #[test]
+    fn decodes_control_bytes() {
+        assert_eq!(
+            decode_key(b"\x1A"),
+            (KeyEvent::new(Key::Z, Modifiers::CTRL), 1)
+        );
+        assert_eq!(
+            decode_key(b"\x00"),
+            (KeyEvent::new(Key::Space, Modifiers::CTRL), 1)
+        );
+        assert_eq!(decode_key(b"\r"), (Key::Enter.into(), 1));
+        assert_eq!(decode_key(b"\t"), (Key::Tab.into(), 1));
+        assert_eq!(decode_key(b"\x7F"), (Key::Backspace.into(), 1));
+    }
+
+    #[test]
+    fn decodes_letters_and_utf8() {
+        assert_eq!(decode_key(b"s"), (Key::S.into(), 1));
+        assert_eq!(
+            decode_key(b"S"),
+            (KeyEvent::new(Key::S, Modifiers::SHIFT), 1)
+        );
+        assert_eq!(decode_key("é".as_bytes()), (Key::Char('é').into(), 2));
+        assert_eq!(decode_key("🐍".as_bytes()), (Key::Char('🐍').into(), 4));
+        assert_eq!(decode_key(b"\xC3"), (Key::Unknown.into(), 1));
+    }
+
+    #[test]
+    fn decodes_incomplete_sequences() {
+        // The first escape is the Escape key, the rest is decoded on its own.
+        assert_eq!(
+            decode_all(b"\x1B\x1B[A"),
+            [Key::Escape.into(), Key::Up.into()]
+        );
+        // Without its code, `ESC O` is Alt+Shift+O.
+        assert_eq!(
+            decode_key(b"\x1BO"),
+            (
+                KeyEvent::new(
+                    Key::O,
+                    Modifiers {
+                        shift: true,
+                        alt: true,
+                        ctrl: false,
+                    }
+                ),
+                2
+            )
+        );
+        // A CSI sequence without its final byte takes the rest of the buffer.
+        assert_eq!(decode_key(b"\x1B[1;5"), (Key::Unknown.into(), 5));
+    }
+
+    #[test]
+    fn decodes_focus_reports() {
+        assert_eq!(decode_event(b"\x1B[I"), (Event::FocusGained, 3));
+        assert_eq!(decode_event(b"\x1B[O"), (Event::FocusLost, 3));
+        assert_eq!(decode_event(b"q"), (Event::Key(Key::Q.into()), 1));
     }
 }
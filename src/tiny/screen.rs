@@ -0,0 +1,248 @@
+use std::fmt::Write;
+
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::{
+    math::{Position, Size},
+    style::{ColorSupport, Style},
+};
+
+const CLEAR: &str = "\x1B[2J\x1B[1;1H";
+
+/// Character of the cell covered by the right half of a wide glyph, the terminal draws nothing for it.
+pub(crate) const WIDE_CONTINUATION: char = '\0';
+
+/// Number of terminal columns taken by a character, 2 for wide glyphs such as most emoji.
+pub fn char_width(c: char) -> usize {
+    c.width().unwrap_or(1).clamp(1, 2)
+}
+
+/// Number of terminal columns taken by a string.
+pub fn str_width(string: &str) -> usize {
+    string.width()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScreenCell {
+    pub c: char,
+    pub style: Style,
+}
+
+impl Default for ScreenCell {
+    fn default() -> Self {
+        ScreenCell {
+            c: ' ',
+            style: Style::default(),
+        }
+    }
+}
+
+/// Back buffer of styled characters the flows draw into, presented to the terminal once per frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Screen {
+    cells: Vec<ScreenCell>,
+    width: usize,
+    height: usize,
+}
+
+impl Screen {
+    pub fn new(width: usize, height: usize) -> Self {
+        Screen {
+            cells: vec![ScreenCell::default(); width * height],
+            width,
+            height,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn size(&self) -> Size {
+        Size::new(self.width, self.height)
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.fill(ScreenCell::default());
+    }
+
+    pub fn get(&self, position: Position) -> Option<ScreenCell> {
+        self.index(position).map(|index| self.cells[index])
+    }
+
+    /// Writes a character, positions outside the screen are ignored.
+    pub fn put_char(&mut self, position: Position, c: char) {
+        self.put_styled_char(position, c, Style::default());
+    }
+
+    /// Writes a character, a wide one also covers the cell on its right
+    /// and is replaced by a space on the last column.
+    pub fn put_styled_char(&mut self, position: Position, c: char, style: Style) {
+        let Some(index) = self.index(position) else {
+            return;
+        };
+
+        self.break_wide_glyph(index);
+        if char_width(c) == 2 {
+            if index % self.width + 1 == self.width {
+                self.cells[index] = ScreenCell { c: ' ', style };
+                return;
+            }
+            self.break_wide_glyph(index + 1);
+            self.cells[index + 1] = ScreenCell {
+                c: WIDE_CONTINUATION,
+                style,
+            };
+        }
+        self.cells[index] = ScreenCell { c, style };
+    }
+
+    /// Writes a string on a single line starting at `position`, clipped to the screen.
+    pub fn put_str(&mut self, position: Position, string: &str) {
+        self.put_styled_str(position, string, Style::default());
+    }
+
+    pub fn put_styled_str(&mut self, position: Position, string: &str, style: Style) {
+        let mut x = position.x;
+        for c in string.chars() {
+            self.put_styled_char(Position { x, y: position.y }, c, style);
+            x += char_width(c) as i32;
+        }
+    }
+
+    /// Writes a string horizontally centred on line `y`.
+    pub fn put_centered_str(&mut self, y: i32, string: &str, style: Style) {
+        let x = (self.width as i32 - str_width(string) as i32) / 2;
+        self.put_styled_str(Position { x, y }, string, style);
+    }
+
+    /// Changes the style of a cell, keeping its character.
+    pub fn set_style(&mut self, position: Position, style: Style) {
+        if let Some(index) = self.index(position) {
+            self.cells[index].style = style;
+        }
+    }
+
+    pub fn lines(&self) -> impl Iterator<Item = String> + '_ {
+        self.cells.chunks(self.width.max(1)).map(|line| {
+            line.iter()
+                .map(|cell| cell.c)
+                .filter(|c| *c != WIDE_CONTINUATION)
+                .collect()
+        })
+    }
+
+    /// Blanks the other half of a wide glyph when the cell at `index` is about to be overwritten.
+    fn break_wide_glyph(&mut self, index: usize) {
+        let x = index % self.width;
+        if self.cells[index].c == WIDE_CONTINUATION && x > 0 {
+            self.cells[index - 1].c = ' ';
+        } else if char_width(self.cells[index].c) == 2 && x + 1 < self.width {
+            self.cells[index + 1].c = ' ';
+        }
+    }
+
+    fn index(&self, position: Position) -> Option<usize> {
+        let (x, y) = (
+            usize::try_from(position.x).ok()?,
+            usize::try_from(position.y).ok()?,
+        );
+        (x < self.width && y < self.height).then_some(x + y * self.width)
+    }
+}
+
+/// Turns screens into the terminal output needed to display them, only emitting what changed since the last frame.
+pub struct Renderer {
+    previous: Screen,
+    needs_clear: bool,
+    color_support: ColorSupport,
+}
+
+impl Renderer {
+    pub fn new() -> Self {
+        Renderer {
+            previous: Screen::new(0, 0),
+            needs_clear: true,
+            color_support: ColorSupport::detect(),
+        }
+    }
+
+    pub fn set_color_support(&mut self, color_support: ColorSupport) {
+        self.color_support = color_support;
+        self.needs_clear = true;
+    }
+
+    /// Returns the escape sequences and text updating the terminal from the previous frame to `screen`.
+    pub fn render(&mut self, screen: &Screen) -> String {
+        let mut output = String::new();
+
+        if self.needs_clear
+            || screen.width != self.previous.width
+            || screen.height != self.previous.height
+        {
+            // A cleared terminal matches a blank screen, diffing against it only emits the non blank runs.
+            output.push_str(CLEAR);
+            self.previous = Screen::new(screen.width, screen.height);
+            self.needs_clear = false;
+        }
+
+        let mut current_style = None;
+        for y in 0..screen.height {
+            let row = y * screen.width..(y + 1) * screen.width;
+            let current = &screen.cells[row.clone()];
+            let previous = &self.previous.cells[row];
+
+            let mut x = 0;
+            while x < screen.width {
+                if current[x] == previous[x] {
+                    x += 1;
+                    continue;
+                }
+
+                // A run starts with a whole glyph, the terminal draws both halves of wide ones.
+                let mut run_start = x;
+                if current[run_start].c == WIDE_CONTINUATION && run_start > 0 {
+                    run_start -= 1;
+                }
+                while x < screen.width && current[x] != previous[x] {
+                    x += 1;
+                }
+
+                let _ = write!(output, "\x1B[{};{}H", y + 1, run_start + 1);
+                for cell in &current[run_start..x] {
+                    if cell.c == WIDE_CONTINUATION {
+                        continue;
+                    }
+                    if current_style != Some(cell.style) {
+                        cell.style.write_sgr(&mut output, self.color_support);
+                        current_style = Some(cell.style);
+                    }
+                    output.push(cell.c);
+                }
+            }
+        }
+
+        if current_style.is_some() {
+            output.push_str("\x1B[0m");
+        }
+
+        self.previous.clone_from(screen);
+
+        output
+    }
+
+    /// Forces the next frame to be drawn from a cleared terminal.
+    pub fn invalidate(&mut self) {
+        self.needs_clear = true;
+    }
+}
+
+impl Default for Renderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
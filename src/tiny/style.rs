@@ -0,0 +1,379 @@
+use std::{env, fmt::Write, str::FromStr};
+
+/// A terminal colour, downgraded to what the terminal supports when rendered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    BrightBlack,
+    BrightRed,
+    BrightGreen,
+    BrightYellow,
+    BrightBlue,
+    BrightMagenta,
+    BrightCyan,
+    BrightWhite,
+    /// One of the 256 colours of the xterm palette.
+    Indexed(u8),
+    Rgb(u8, u8, u8),
+}
+
+/// Foreground, background and attributes of a screen cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Style {
+    pub foreground: Option<Color>,
+    pub background: Option<Color>,
+    pub bold: bool,
+    pub dim: bool,
+    pub underline: bool,
+    pub reverse: bool,
+}
+
+/// Colours the terminal can display, from none to 24 bit truecolor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorSupport {
+    NoColor,
+    Ansi16,
+    Ansi256,
+    TrueColor,
+}
+
+const ANSI_16_RGB: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+impl Color {
+    /// Index of the colour in the 16 colour palette, if it is one of them.
+    fn ansi_index(self) -> Option<u8> {
+        let index = match self {
+            Color::Black => 0,
+            Color::Red => 1,
+            Color::Green => 2,
+            Color::Yellow => 3,
+            Color::Blue => 4,
+            Color::Magenta => 5,
+            Color::Cyan => 6,
+            Color::White => 7,
+            Color::BrightBlack => 8,
+            Color::BrightRed => 9,
+            Color::BrightGreen => 10,
+            Color::BrightYellow => 11,
+            Color::BrightBlue => 12,
+            Color::BrightMagenta => 13,
+            Color::BrightCyan => 14,
+            Color::BrightWhite => 15,
+            Color::Indexed(index) if index < 16 => index,
+            _ => return None,
+        };
+        Some(index)
+    }
+
+    fn from_ansi_index(index: u8) -> Self {
+        [
+            Color::Black,
+            Color::Red,
+            Color::Green,
+            Color::Yellow,
+            Color::Blue,
+            Color::Magenta,
+            Color::Cyan,
+            Color::White,
+            Color::BrightBlack,
+            Color::BrightRed,
+            Color::BrightGreen,
+            Color::BrightYellow,
+            Color::BrightBlue,
+            Color::BrightMagenta,
+            Color::BrightCyan,
+            Color::BrightWhite,
+        ][index as usize % 16]
+    }
+
+    fn to_rgb(self) -> (u8, u8, u8) {
+        match self {
+            Color::Rgb(r, g, b) => (r, g, b),
+            Color::Indexed(index) if index >= 232 => {
+                let level = 8 + (index - 232) * 10;
+                (level, level, level)
+            }
+            Color::Indexed(index) if index >= 16 => {
+                let cube = index - 16;
+                let level = |value: u8| if value == 0 { 0 } else { 55 + value * 40 };
+                (level(cube / 36), level((cube / 6) % 6), level(cube % 6))
+            }
+            color => ANSI_16_RGB[color.ansi_index().unwrap_or(0) as usize],
+        }
+    }
+
+    /// Returns the closest colour the terminal can display, or `None` if it has no colours.
+    pub fn downgrade(self, support: ColorSupport) -> Option<Color> {
+        match support {
+            ColorSupport::NoColor => None,
+            ColorSupport::TrueColor => Some(self),
+            ColorSupport::Ansi256 => match self {
+                Color::Rgb(r, g, b) => Some(Color::Indexed(rgb_to_256(r, g, b))),
+                color => Some(color),
+            },
+            ColorSupport::Ansi16 => match self.ansi_index() {
+                Some(_) => Some(self),
+                None => {
+                    let (r, g, b) = self.to_rgb();
+                    Some(Color::from_ansi_index(rgb_to_16(r, g, b)))
+                }
+            },
+        }
+    }
+
+    fn write_sgr(self, output: &mut String, background: bool) {
+        match (self.ansi_index(), self) {
+            (Some(index), _) => {
+                let base = match (background, index < 8) {
+                    (false, true) => 30,
+                    (false, false) => 90 - 8,
+                    (true, true) => 40,
+                    (true, false) => 100 - 8,
+                };
+                let _ = write!(output, ";{}", base + index as u32);
+            }
+            (None, Color::Rgb(r, g, b)) => {
+                let _ = write!(
+                    output,
+                    ";{};2;{r};{g};{b}",
+                    if background { 48 } else { 38 }
+                );
+            }
+            (None, Color::Indexed(index)) => {
+                let _ = write!(output, ";{};5;{index}", if background { 48 } else { 38 });
+            }
+            (None, _) => {}
+        }
+    }
+}
+
+fn rgb_to_256(r: u8, g: u8, b: u8) -> u8 {
+    // Greys map to the grey ramp, which is finer than the colour cube.
+    if r == g && g == b {
+        return match r {
+            0..=7 => 16,
+            248..=255 => 231,
+            _ => 232 + (r - 8) / 10,
+        };
+    }
+
+    let to_cube = |value: u8| {
+        if value < 48 {
+            0
+        } else if value < 115 {
+            1
+        } else {
+            (value - 35) / 40
+        }
+    };
+    16 + 36 * to_cube(r) + 6 * to_cube(g) + to_cube(b)
+}
+
+fn rgb_to_16(r: u8, g: u8, b: u8) -> u8 {
+    let distance = |(pr, pg, pb): (u8, u8, u8)| {
+        let dr = r as i32 - pr as i32;
+        let dg = g as i32 - pg as i32;
+        let db = b as i32 - pb as i32;
+        dr * dr + dg * dg + db * db
+    };
+
+    (0..16u8)
+        .min_by_key(|index| distance(ANSI_16_RGB[*index as usize]))
+        .unwrap_or(0)
+}
+
+/// Names of the 16 colour palette, in palette order.
+const COLOR_NAMES: [&str; 16] = [
+    "black",
+    "red",
+    "green",
+    "yellow",
+    "blue",
+    "magenta",
+    "cyan",
+    "white",
+    "bright_black",
+    "bright_red",
+    "bright_green",
+    "bright_yellow",
+    "bright_blue",
+    "bright_magenta",
+    "bright_cyan",
+    "bright_white",
+];
+
+impl FromStr for Color {
+    type Err = String;
+
+    /// Parses a palette name such as `bright_red`, an xterm index from 0 to 255 or `#rrggbb`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_lowercase().replace(['-', ' '], "_");
+        if let Some(index) = COLOR_NAMES.iter().position(|color| *color == name) {
+            return Ok(Color::from_ansi_index(index as u8));
+        }
+
+        if name == "gray" || name == "grey" {
+            return Ok(Color::BrightBlack);
+        }
+
+        if let Some(hex) = name
+            .strip_prefix('#')
+            .filter(|hex| hex.len() == 6 && hex.is_ascii())
+        {
+            let channel = |range| u8::from_str_radix(&hex[range], 16);
+            if let (Ok(r), Ok(g), Ok(b)) = (channel(0..2), channel(2..4), channel(4..6)) {
+                return Ok(Color::Rgb(r, g, b));
+            }
+        }
+
+        name.parse()
+            .map(Color::Indexed)
+            .map_err(|_| format!("Unknown colour '{s}'"))
+    }
+}
+
+impl Style {
+    pub const fn new() -> Self {
+        Style {
+            foreground: None,
+            background: None,
+            bold: false,
+            dim: false,
+            underline: false,
+            reverse: false,
+        }
+    }
+
+    pub const fn fg(mut self, color: Color) -> Self {
+        self.foreground = Some(color);
+        self
+    }
+
+    pub const fn bg(mut self, color: Color) -> Self {
+        self.background = Some(color);
+        self
+    }
+
+    pub const fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    pub const fn dim(mut self) -> Self {
+        self.dim = true;
+        self
+    }
+
+    pub const fn underline(mut self) -> Self {
+        self.underline = true;
+        self
+    }
+
+    pub const fn reverse(mut self) -> Self {
+        self.reverse = true;
+        self
+    }
+
+    /// Writes the SGR escape sequence switching the terminal to this style.
+    pub fn write_sgr(&self, output: &mut String, support: ColorSupport) {
+        // Always start from a reset so that attributes of the previous style don't leak.
+        output.push_str("\x1B[0");
+        if self.bold {
+            output.push_str(";1");
+        }
+        if self.dim {
+            output.push_str(";2");
+        }
+        if self.underline {
+            output.push_str(";4");
+        }
+        if self.reverse {
+            output.push_str(";7");
+        }
+        if let Some(color) = self.foreground.and_then(|color| color.downgrade(support)) {
+            color.write_sgr(output, false);
+        }
+        if let Some(color) = self.background.and_then(|color| color.downgrade(support)) {
+            color.write_sgr(output, true);
+        }
+        output.push('m');
+    }
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ColorSupport {
+    /// Detects the colour support from `NO_COLOR`, `COLORTERM` and `TERM`.
+    pub fn detect() -> Self {
+        if env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+            return ColorSupport::NoColor;
+        }
+
+        let colorterm = env::var("COLORTERM").unwrap_or_default();
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            return ColorSupport::TrueColor;
+        }
+
+        let term = env::var("TERM").unwrap_or_default();
+        if term.is_empty() || term == "dumb" {
+            ColorSupport::NoColor
+        } else if term.contains("truecolor") || term.contains("direct") {
+            ColorSupport::TrueColor
+        } else if term.contains("256color") {
+            ColorSupport::Ansi256
+        } else {
+            ColorSupport::Ansi16
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_colours() {
+        assert_eq!("bright-red".parse(), Ok(Color::BrightRed));
+        assert_eq!("Grey".parse(), Ok(Color::BrightBlack));
+        assert_eq!("208".parse(), Ok(Color::Indexed(208)));
+        assert_eq!("#FF8000".parse(), Ok(Color::Rgb(255, 128, 0)));
+    }
+
+    #[test]
+    fn rejects_bad_colours() {
+        for colour in ["#aébcd", "#12345", "#gg0000", "256", "pink"] {
+            assert_eq!(
+                colour.parse::<Color>(),
+                Err(format!("Unknown colour '{colour}'"))
+            );
+        }
+    }
+}
@@ -0,0 +1,227 @@
+use std::{fmt, str::FromStr};
+
+use crate::{
+    canvas::Canvas,
+    grid::Grid,
+    math::{Direction, Position, Rect, Size},
+    screen::char_width,
+    style::Style,
+    viewport::Viewport,
+};
+
+/// How the cells of a board are drawn, chosen with the `tileset` setting.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Tileset {
+    /// One ASCII character per cell, for any terminal.
+    #[default]
+    Ascii,
+    /// One character per cell, using block and geometric shapes.
+    Unicode,
+    /// Two columns per cell, so that cells are about square instead of twice as tall as wide.
+    Wide,
+}
+
+/// How walls are drawn, chosen with the `walls` setting.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Walls {
+    /// The glyph of the wall tiles.
+    Solid,
+    /// Lines joining each wall to its neighbours, `+`, `-` and `|` with the ASCII tileset.
+    #[default]
+    Light,
+    Heavy,
+    Double,
+}
+
+/// Cells which can be drawn with any tileset.
+pub trait Tile {
+    /// Characters of the cell, one column wide except with [`Tileset::Wide`] where they take two.
+    fn glyph(&self, tileset: Tileset) -> &str;
+
+    /// Whether the cell is drawn joined to the neighbouring walls.
+    fn is_wall(&self) -> bool {
+        false
+    }
+}
+
+impl Tileset {
+    pub const ALL: [Tileset; 3] = [Tileset::Ascii, Tileset::Unicode, Tileset::Wide];
+
+    /// Number of columns taken by a cell.
+    pub fn cell_width(self) -> usize {
+        match self {
+            Tileset::Ascii | Tileset::Unicode => 1,
+            Tileset::Wide => 2,
+        }
+    }
+
+    /// Number of cells fitting in an area of the screen.
+    pub fn cells_in(self, size: Size) -> Size {
+        Size::new(size.width / self.cell_width(), size.height)
+    }
+
+    /// Screen area taken by the cell drawn at `position`.
+    pub fn cell_area(self, position: Position) -> Rect {
+        let origin = Position {
+            x: position.x * self.cell_width() as i32,
+            y: position.y,
+        };
+        Rect::new(origin, Size::new(self.cell_width(), 1))
+    }
+
+    /// Draws a cell at a position in cells.
+    pub fn draw_tile(
+        self,
+        canvas: &mut Canvas,
+        position: Position,
+        tile: &impl Tile,
+        style: Style,
+    ) {
+        self.draw_glyph(canvas, position, tile.glyph(self), style);
+    }
+
+    /// Draws the part of a grid visible through a viewport sized with [`Tileset::cells_in`],
+    /// walls are joined to their neighbours unless they are `Solid`.
+    pub fn draw_grid<T: Tile>(
+        self,
+        canvas: &mut Canvas,
+        grid: &Grid<T>,
+        viewport: &Viewport,
+        walls: Walls,
+        style: impl Fn(&T) -> Style,
+    ) {
+        let bounds = Rect::new(Position { x: 0, y: 0 }, grid.size());
+        let visible = viewport.visible().intersection(bounds);
+        for y in 0..visible.size.height as i32 {
+            for x in 0..visible.size.width as i32 {
+                let position = visible.origin + Direction { x, y };
+                let Some(cell) = grid.get(position) else {
+                    continue;
+                };
+
+                let at = viewport.to_screen(position);
+                if walls == Walls::Solid || !cell.is_wall() {
+                    self.draw_tile(canvas, at, cell, style(cell));
+                    continue;
+                }
+
+                let is_wall = |x, y| {
+                    grid.get(position + Direction { x, y })
+                        .is_some_and(Tile::is_wall)
+                };
+                let neighbours = [is_wall(0, -1), is_wall(1, 0), is_wall(0, 1), is_wall(-1, 0)];
+                let glyph = walls.joined(self, neighbours);
+                self.draw_glyph(canvas, at, &glyph, style(cell));
+            }
+        }
+    }
+
+    /// Draws characters in a cell, padded with spaces or cut to the cell width.
+    fn draw_glyph(self, canvas: &mut Canvas, position: Position, glyph: &str, style: Style) {
+        let origin = self.cell_area(position).origin;
+        let at = |column: usize| Position {
+            x: origin.x + column as i32,
+            y: origin.y,
+        };
+
+        let mut column = 0;
+        for c in glyph.chars() {
+            let width = char_width(c);
+            if column + width > self.cell_width() {
+                break;
+            }
+            canvas.put_styled_char(at(column), c, style);
+            column += width;
+        }
+        for column in column..self.cell_width() {
+            canvas.put_styled_char(at(column), ' ', style);
+        }
+    }
+}
+
+impl fmt::Display for Tileset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Tileset::Ascii => "ascii",
+            Tileset::Unicode => "unicode",
+            Tileset::Wide => "wide",
+        })
+    }
+}
+
+impl FromStr for Tileset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Tileset::ALL
+            .into_iter()
+            .find(|tileset| tileset.to_string() == s.trim().to_lowercase())
+            .ok_or_else(|| format!("Unknown tileset '{s}', expected ascii, unicode or wide"))
+    }
+}
+
+impl Walls {
+    pub const ALL: [Walls; 4] = [Walls::Solid, Walls::Light, Walls::Heavy, Walls::Double];
+
+    /// The lone wall, the horizontal and vertical lines, the corners `┌ ┐ └ ┘`,
+    /// the tees `├ ┤ ┬ ┴` and the cross.
+    fn glyphs(self, tileset: Tileset) -> [char; 12] {
+        match (self, tileset) {
+            (Walls::Solid, _) | (_, Tileset::Ascii) => {
+                ['+', '-', '|', '+', '+', '+', '+', '+', '+', '+', '+', '+']
+            }
+            (Walls::Light, _) => ['■', '─', '│', '┌', '┐', '└', '┘', '├', '┤', '┬', '┴', '┼'],
+            (Walls::Heavy, _) => ['■', '━', '┃', '┏', '┓', '┗', '┛', '┣', '┫', '┳', '┻', '╋'],
+            (Walls::Double, _) => ['■', '═', '║', '╔', '╗', '╚', '╝', '╠', '╣', '╦', '╩', '╬'],
+        }
+    }
+
+    /// Glyph of a wall given which of its neighbours above, right, below and left are walls.
+    /// With the wide tileset the second column continues the line to the right.
+    fn joined(self, tileset: Tileset, [up, right, down, left]: [bool; 4]) -> String {
+        let glyphs = self.glyphs(tileset);
+        let index = match (up, right, down, left) {
+            (false, false, false, false) => 0,
+            (false, _, false, _) => 1,
+            (_, false, _, false) => 2,
+            (false, true, true, false) => 3,
+            (false, false, true, true) => 4,
+            (true, true, false, false) => 5,
+            (true, false, false, true) => 6,
+            (true, true, true, false) => 7,
+            (true, false, true, true) => 8,
+            (false, true, true, true) => 9,
+            (true, true, false, true) => 10,
+            (true, true, true, true) => 11,
+        };
+        let (c, horizontal) = (glyphs[index], glyphs[1]);
+
+        match tileset {
+            Tileset::Wide if right => format!("{c}{horizontal}"),
+            Tileset::Wide => format!("{c} "),
+            Tileset::Ascii | Tileset::Unicode => c.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Walls {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Walls::Solid => "solid",
+            Walls::Light => "light",
+            Walls::Heavy => "heavy",
+            Walls::Double => "double",
+        })
+    }
+}
+
+impl FromStr for Walls {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Walls::ALL
+            .into_iter()
+            .find(|walls| walls.to_string() == s.trim().to_lowercase())
+            .ok_or_else(|| format!("Unknown walls '{s}', expected solid, light, heavy or double"))
+    }
+}
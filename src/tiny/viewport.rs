@@ -0,0 +1,120 @@
+use crate::math::{Direction, Position, Rect, Size};
+
+/// Camera showing the part of a world, such as a board, which fits on the screen.
+///
+/// The camera scrolls when the followed target leaves the dead zone in the middle of the
+/// view, and never further than the edges of the world. A world which fits on the screen
+/// is shown from its top left corner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Viewport {
+    world: Size,
+    size: Size,
+    /// World position shown at the top left of the view.
+    offset: Position,
+    /// Area around the middle of the view where the target moves without scrolling,
+    /// half of the view when not set.
+    dead_zone: Option<Size>,
+}
+
+impl Viewport {
+    /// A view of the whole world until it is resized.
+    pub fn new(world: Size) -> Self {
+        Viewport {
+            world,
+            size: world,
+            offset: Position { x: 0, y: 0 },
+            dead_zone: None,
+        }
+    }
+
+    pub fn with_dead_zone(mut self, dead_zone: Size) -> Self {
+        self.dead_zone = Some(dead_zone);
+        self
+    }
+
+    pub fn size(&self) -> Size {
+        self.size
+    }
+
+    pub fn offset(&self) -> Position {
+        self.offset
+    }
+
+    /// Changes the size of the view, typically to the size of the terminal.
+    pub fn resize(&mut self, size: Size) {
+        self.size = size;
+        self.offset = self.clamped(self.offset);
+    }
+
+    /// Scrolls so that `target` is inside the dead zone.
+    pub fn follow(&mut self, target: Position) {
+        let dead_zone = self
+            .dead_zone
+            .unwrap_or(Size::new(self.size.width / 2, self.size.height / 2));
+        let offset = Position {
+            x: follow_axis(self.offset.x, target.x, self.size.width, dead_zone.width),
+            y: follow_axis(self.offset.y, target.y, self.size.height, dead_zone.height),
+        };
+        self.offset = self.clamped(offset);
+    }
+
+    /// Scrolls so that `target` is in the middle of the view.
+    pub fn center_on(&mut self, target: Position) {
+        let offset = Position {
+            x: target.x - self.size.width as i32 / 2,
+            y: target.y - self.size.height as i32 / 2,
+        };
+        self.offset = self.clamped(offset);
+    }
+
+    /// Part of the world which is visible.
+    pub fn visible(&self) -> Rect {
+        Rect::new(self.offset, self.size)
+    }
+
+    pub fn is_visible(&self, position: Position) -> bool {
+        self.visible().contains(position)
+    }
+
+    /// Where a world position is drawn on the screen.
+    pub fn to_screen(&self, position: Position) -> Position {
+        position - self.scroll()
+    }
+
+    /// World position drawn at a screen position.
+    pub fn to_world(&self, position: Position) -> Position {
+        position + self.scroll()
+    }
+
+    /// How far the view is scrolled from the top left of the world.
+    fn scroll(&self) -> Direction {
+        Direction {
+            x: self.offset.x,
+            y: self.offset.y,
+        }
+    }
+
+    /// Keeps the view inside the world.
+    fn clamped(&self, offset: Position) -> Position {
+        let max = |world: usize, size: usize| (world as i32 - size as i32).max(0);
+        Position {
+            x: offset.x.clamp(0, max(self.world.width, self.size.width)),
+            y: offset.y.clamp(0, max(self.world.height, self.size.height)),
+        }
+    }
+}
+
+/// Offset along one axis keeping `target` between the edges of the dead zone.
+fn follow_axis(offset: i32, target: i32, size: usize, dead_zone: usize) -> i32 {
+    let dead_zone = dead_zone.min(size).max(1) as i32;
+    let start = offset + (size as i32 - dead_zone) / 2;
+    let end = start + dead_zone;
+
+    if target < start {
+        offset - (start - target)
+    } else if target >= end {
+        offset + (target - end + 1)
+    } else {
+        offset
+    }
+}
@@ -0,0 +1,84 @@
+//! Runs sokoban from its intro without a terminal, on a virtual clock.
+
+use tiny::{
+    backend::HeadlessBackend,
+    banner::Font,
+    clock::VirtualClock,
+    config::Config,
+    flow::{GameLauncher, IntroFlow},
+    prelude::*,
+    sokoban::flows::SokobanLauncher,
+};
+
+#[test]
+fn plays_the_first_level_from_the_intro() {
+    let backend = HeadlessBackend::new(Size::new(60, 24))
+        .wait(1)
+        .press(Key::Enter)
+        .wait(1)
+        .press(Key::D)
+        .wait(1)
+        .press(Key::D)
+        .wait(1);
+    let frames = backend.frames();
+    let launcher = SokobanLauncher::from_config(&Config::new()).unwrap();
+    let mut app = TinyApp::new()
+        .with_backend(backend)
+        .with_clock(VirtualClock::new())
+        .with_flow(IntroFlow::new("Sokoban", launcher));
+
+    app.run().unwrap();
+
+    assert_eq!(frames.len(), 4);
+    let lines = |index: usize| -> Vec<String> { frames.get(index).unwrap().lines().collect() };
+
+    let intro = lines(0);
+    for title_line in Font::block().render("Tiny Sokoban") {
+        assert!(intro.iter().any(|line| line.contains(&title_line)));
+    }
+    assert!(intro.iter().any(|line| line.contains("Any key to start!")));
+
+    // The first level is drawn at the top left, the player walks up to the box.
+    assert!(lines(1)[1].starts_with("@ QX "));
+    assert!(lines(2)[1].starts_with(" @QX "));
+
+    // Pushing the box onto the target starts the next level.
+    assert_eq!(
+        &lines(3)[..3],
+        ["+----+", "| @  |", "|X   |"].map(|line| format!("{line:<60}"))
+    );
+}
+
+#[test]
+fn reads_two_moves_in_one_frame() {
+    // Finishes the first level, then pushes the box of the second one down against the wall
+    // with two presses read in the same frame.
+    let backend = HeadlessBackend::new(Size::new(60, 24))
+        .wait(1)
+        .press(Key::Enter)
+        .wait(1)
+        .press(Key::D)
+        .press(Key::D)
+        .wait(1)
+        .press(Key::D)
+        .press(Key::S)
+        .wait(1)
+        .press(Key::S)
+        .press(Key::S)
+        .wait(1);
+    let frames = backend.frames();
+    let launcher = SokobanLauncher::from_config(&Config::new()).unwrap();
+    let mut app = TinyApp::new()
+        .with_backend(backend)
+        .with_clock(VirtualClock::new())
+        .with_flow(IntroFlow::new("Sokoban", launcher));
+
+    app.run().unwrap();
+
+    // The second press finds the box against the wall instead of walking onto it.
+    let lines: Vec<String> = frames.get(frames.len() - 1).unwrap().lines().collect();
+    assert_eq!(
+        &lines[3..6],
+        ["|  @ |", "|  Q |", "+----+"].map(|line| format!("{line:<60}"))
+    );
+}